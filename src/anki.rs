@@ -0,0 +1,709 @@
+// anki.rs
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use color_eyre::{eyre::eyre, Result};
+use rusqlite::Connection;
+use serde::Deserialize;
+use sha1::{Digest, Sha1};
+use crate::export::card_key;
+use crate::load::get_flashcards_dir;
+use crate::model::{Card, Deck, ExampleSentence, ReviewHistory};
+use crate::utilities::{current_unix_time, escape_html, stable_hash, unique_card_path};
+
+const DAY: u64 = 24 * 60 * 60;
+const FIELD_SEPARATOR: char = '\x1f';
+
+// Newest first: .anki21b is a zstd-compressed collection, the others are plain SQLite
+const COLLECTION_NAMES: [&str; 3] = ["collection.anki21b", "collection.anki21", "collection.anki2"];
+
+struct NoteType {
+    fields: Vec<String>,
+    templates: usize,
+}
+
+struct AnkiCard {
+    id: i64,
+    deck_id: i64,
+    ord: i64,
+    card_type: i64,
+    due: i64,
+    ivl: i64,
+}
+
+struct AnkiReview {
+    id: i64,
+    ease: i64,
+    ivl: i64,
+}
+
+#[derive(Deserialize)]
+struct JsonNoteType {
+    flds: Vec<JsonField>,
+    tmpls: Vec<serde_json::Value>,
+}
+
+#[derive(Deserialize)]
+struct JsonField {
+    name: String,
+    ord: usize,
+}
+
+#[derive(Deserialize)]
+struct JsonDeck {
+    name: String,
+}
+
+pub fn import_anki(package: &Path, scheduling: bool, history: bool) -> Result<()> {
+    let collection_path = extract_collection(package)?;
+    let result = Connection::open(&collection_path)
+        .map_err(Into::into)
+        .and_then(|conn| import_collection(&conn, scheduling, history));
+    fs::remove_file(&collection_path)?;
+
+    let (notes, decks) = result?;
+    println!("Imported {} notes into {} decks", notes, decks);
+    Ok(())
+}
+
+// SQLite needs a real file, so the collection is unpacked into the temp directory
+fn extract_collection(package: &Path) -> Result<PathBuf> {
+    let mut archive = zip::ZipArchive::new(File::open(package)?)?;
+
+    for name in COLLECTION_NAMES {
+        let Ok(mut entry) = archive.by_name(name) else {
+            continue;
+        };
+        let mut contents = Vec::new();
+        entry.read_to_end(&mut contents)?;
+        if name.ends_with('b') {
+            contents = zstd::decode_all(contents.as_slice())?;
+        }
+
+        let path = std::env::temp_dir().join(format!("kkameokda-import-{}.sqlite", std::process::id()));
+        fs::write(&path, contents)?;
+        return Ok(path);
+    }
+
+    Err(eyre!("No Anki collection found in {:?}", package))
+}
+
+fn import_collection(conn: &Connection, scheduling: bool, history: bool) -> Result<(usize, usize)> {
+    let flashcards_dir = get_flashcards_dir();
+    let note_types = load_note_types(conn)?;
+    let decks = load_deck_names(conn)?;
+    let created: i64 = conn.query_row("SELECT crt FROM col", [], |row| row.get(0))?;
+
+    let mut cards_by_note: HashMap<i64, Vec<AnkiCard>> = HashMap::new();
+    let mut stmt = conn.prepare("SELECT id, nid, did, ord, type, due, ivl FROM cards ORDER BY nid, ord")?;
+    let mut rows = stmt.query([])?;
+    while let Some(row) = rows.next()? {
+        cards_by_note.entry(row.get(1)?).or_default().push(AnkiCard {
+            id: row.get(0)?,
+            deck_id: row.get(2)?,
+            ord: row.get(3)?,
+            card_type: row.get(4)?,
+            due: row.get(5)?,
+            ivl: row.get(6)?,
+        });
+    }
+
+    let mut reviews_by_card: HashMap<i64, Vec<AnkiReview>> = HashMap::new();
+    if history {
+        let mut stmt = conn.prepare("SELECT id, cid, ease, ivl FROM revlog ORDER BY id")?;
+        let mut rows = stmt.query([])?;
+        while let Some(row) = rows.next()? {
+            reviews_by_card.entry(row.get(1)?).or_default().push(AnkiReview {
+                id: row.get(0)?,
+                ease: row.get(2)?,
+                ivl: row.get(3)?,
+            });
+        }
+    }
+
+    let mut imported = 0;
+    let mut used_decks = Vec::new();
+    let mut stmt = conn.prepare("SELECT id, mid, flds, tags FROM notes ORDER BY id")?;
+    let mut rows = stmt.query([])?;
+    while let Some(row) = rows.next()? {
+        let note_id: i64 = row.get(0)?;
+        let note_type_id: i64 = row.get(1)?;
+        let fields: String = row.get(2)?;
+        let fields: Vec<&str> = fields.split(FIELD_SEPARATOR).collect();
+
+        let tags: String = row.get(3)?;
+
+        let mut card = note_to_card(note_types.get(&note_type_id), &fields);
+        card.tags = tags.split_whitespace().map(String::from).collect();
+        // Note IDs are the time the note was created, in milliseconds
+        card.added = u64::try_from(note_id / 1000).ok();
+        let anki_cards = cards_by_note.get(&note_id).map(Vec::as_slice).unwrap_or_default();
+
+        if scheduling {
+            if let Some(anki_card) = anki_cards.first() {
+                apply_scheduling(&mut card, anki_card, created);
+            }
+        }
+        if history {
+            let mut reviews: Vec<ReviewHistory> = anki_cards.iter()
+                .flat_map(|anki_card| {
+                    reviews_by_card.get(&anki_card.id)
+                        .into_iter()
+                        .flatten()
+                        .map(move |review| review_history(review, anki_card.ord == 1))
+                })
+                .collect();
+            reviews.sort_by_key(|review| review.date);
+            if !reviews.is_empty() {
+                card.history = Some(reviews);
+            }
+        }
+
+        let deck_components = anki_cards.first()
+            .and_then(|anki_card| decks.get(&anki_card.deck_id))
+            .cloned()
+            .unwrap_or_else(|| vec!["Default".to_string()]);
+        let deck_dir = deck_components.iter()
+            .fold(flashcards_dir.clone(), |dir, component| dir.join(safe_deck_name(component)));
+
+        fs::create_dir_all(&deck_dir)?;
+        let path = unique_card_path(&deck_dir, &card.front, "yaml");
+        fs::write(&path, serde_yaml::to_string(&card)?)?;
+
+        if !used_decks.contains(&deck_dir) {
+            used_decks.push(deck_dir);
+        }
+        imported += 1;
+    }
+
+    Ok((imported, used_decks.len()))
+}
+
+fn table_exists(conn: &Connection, name: &str) -> Result<bool> {
+    let count: i64 = conn.query_row(
+        "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = ?1",
+        [name],
+        |row| row.get(0),
+    )?;
+    Ok(count > 0)
+}
+
+fn load_note_types(conn: &Connection) -> Result<HashMap<i64, NoteType>> {
+    let mut note_types = HashMap::new();
+
+    // Collections from Anki 2.1.28 onwards keep note types in their own tables
+    if table_exists(conn, "notetypes")? {
+        let mut stmt = conn.prepare("SELECT ntid, name FROM fields ORDER BY ntid, ord")?;
+        let mut rows = stmt.query([])?;
+        while let Some(row) = rows.next()? {
+            note_types.entry(row.get(0)?)
+                .or_insert_with(|| NoteType { fields: Vec::new(), templates: 0 })
+                .fields
+                .push(row.get(1)?);
+        }
+
+        let mut stmt = conn.prepare("SELECT ntid, COUNT(*) FROM templates GROUP BY ntid")?;
+        let mut rows = stmt.query([])?;
+        while let Some(row) = rows.next()? {
+            if let Some(note_type) = note_types.get_mut(&row.get::<_, i64>(0)?) {
+                note_type.templates = row.get::<_, i64>(1)? as usize;
+            }
+        }
+    } else {
+        let models: String = conn.query_row("SELECT models FROM col", [], |row| row.get(0))?;
+        let models: HashMap<String, JsonNoteType> = serde_json::from_str(&models)?;
+        for (id, mut model) in models {
+            model.flds.sort_by_key(|field| field.ord);
+            note_types.insert(id.parse()?, NoteType {
+                fields: model.flds.into_iter().map(|field| field.name).collect(),
+                templates: model.tmpls.len(),
+            });
+        }
+    }
+
+    Ok(note_types)
+}
+
+// Maps deck IDs to the components of their `Parent::Child` names
+fn load_deck_names(conn: &Connection) -> Result<HashMap<i64, Vec<String>>> {
+    let mut decks = HashMap::new();
+
+    if table_exists(conn, "decks")? {
+        let mut stmt = conn.prepare("SELECT id, name FROM decks")?;
+        let mut rows = stmt.query([])?;
+        while let Some(row) = rows.next()? {
+            let name: String = row.get(1)?;
+            decks.insert(row.get(0)?, name.split(FIELD_SEPARATOR).map(String::from).collect());
+        }
+    } else {
+        let json: String = conn.query_row("SELECT decks FROM col", [], |row| row.get(0))?;
+        let json: HashMap<String, JsonDeck> = serde_json::from_str(&json)?;
+        for (id, deck) in json {
+            decks.insert(id.parse()?, deck.name.split("::").map(String::from).collect());
+        }
+    }
+
+    Ok(decks)
+}
+
+fn note_to_card(note_type: Option<&NoteType>, fields: &[&str]) -> Card {
+    let first = fields.first().copied().unwrap_or_default();
+    let is_cloze = first.contains("{{c");
+
+    let mut card = if is_cloze {
+        Card::new(strip_html(&render_cloze(first, false)), strip_html(&render_cloze(first, true)))
+    } else {
+        Card::new(strip_html(first), strip_html(fields.get(1).copied().unwrap_or_default()))
+    };
+    card.reversible = !is_cloze && note_type.is_some_and(|note_type| note_type.templates > 1);
+
+    // Fields after the ones used for the front and back go to the explanation
+    // and notes, by name when the note type has matching fields
+    let mut notes = Vec::new();
+    let remaining = if is_cloze { 1 } else { 2 };
+    for (index, value) in fields.iter().enumerate().skip(remaining) {
+        let value = strip_html(value);
+        if value.is_empty() {
+            continue;
+        }
+        let name = note_type.and_then(|note_type| note_type.fields.get(index)).map(String::as_str);
+        match name.map(str::to_lowercase).as_deref() {
+            Some("explanation") if card.explanation.is_none() => card.explanation = Some(value),
+            Some("examples") if card.examples.is_none() => card.examples = Some(parse_examples(&value)),
+            Some("notes" | "note" | "extra" | "back extra") => notes.push(value),
+            _ if card.explanation.is_none() => card.explanation = Some(value),
+            Some(_) => notes.push(format!("{}: {}", name.unwrap_or_default(), value)),
+            None => notes.push(value),
+        }
+    }
+    if !notes.is_empty() {
+        card.notes = Some(notes.join("\n"));
+    }
+
+    card
+}
+
+// One example per line, as `sentence - translation`
+fn parse_examples(text: &str) -> Vec<ExampleSentence> {
+    text.lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            let (sentence, translation) = line.split_once(" - ").unwrap_or((line, ""));
+            ExampleSentence {
+                sentence: sentence.trim().to_string(),
+                translation: translation.trim().to_string(),
+            }
+        })
+        .collect()
+}
+
+fn apply_scheduling(card: &mut Card, anki_card: &AnkiCard, created: i64) {
+    match anki_card.card_type {
+        // Review cards are due in days since the collection was created
+        2 => {
+            card.next_review = Some((created + anki_card.due * DAY as i64).max(0) as u64);
+            card.ease_factor = Some(anki_card.ivl.max(1) as u64 * DAY);
+        }
+        // Learning cards are due at a UNIX timestamp
+        1 | 3 => {
+            card.next_review = Some(anki_card.due.max(0) as u64);
+            card.ease_factor = Some(DAY);
+        }
+        _ => {}
+    }
+}
+
+fn review_history(review: &AnkiReview, reversed: bool) -> ReviewHistory {
+    ReviewHistory {
+        date: (review.id / 1000) as u64,
+        remembered: review.ease > 1,
+        reversed: Some(reversed),
+        interval: Some(if review.ivl < 0 {
+            review.ivl.unsigned_abs()
+        } else {
+            review.ivl as u64 * DAY
+        }),
+        partial: (review.ease == 2).then_some(true),
+    }
+}
+
+fn safe_deck_name(name: &str) -> String {
+    let name: String = name.trim()
+        .chars()
+        .map(|c| if matches!(c, '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|') { '_' } else { c })
+        .collect();
+    if name.is_empty() || name.starts_with('.') {
+        format!("_{}", name)
+    } else {
+        name
+    }
+}
+
+// `{{c1::answer::hint}}` becomes `[hint]` (or `[...]`) when hidden and `answer` when revealed
+fn render_cloze(text: &str, reveal: bool) -> String {
+    let mut rendered = String::new();
+    let mut rest = text;
+
+    while let Some(start) = rest.find("{{c") {
+        let after = &rest[start + 3..];
+        let digits = after.chars().take_while(char::is_ascii_digit).count();
+        let Some(body) = after[digits..].strip_prefix("::").filter(|_| digits > 0) else {
+            rendered.push_str(&rest[..start + 3]);
+            rest = after;
+            continue;
+        };
+        let Some(end) = body.find("}}") else {
+            break;
+        };
+
+        let (answer, hint) = match body[..end].split_once("::") {
+            Some((answer, hint)) => (answer, Some(hint)),
+            None => (&body[..end], None),
+        };
+        rendered.push_str(&rest[..start]);
+        if reveal {
+            rendered.push_str(answer);
+        } else {
+            rendered.push('[');
+            rendered.push_str(hint.unwrap_or("..."));
+            rendered.push(']');
+        }
+        rest = &body[end + 2..];
+    }
+
+    rendered.push_str(rest);
+    rendered
+}
+
+fn strip_html(html: &str) -> String {
+    let mut text = String::new();
+    let mut tag = String::new();
+    let mut in_tag = false;
+
+    for c in html.chars() {
+        if in_tag {
+            if c == '>' {
+                in_tag = false;
+                let name = tag.trim_start_matches('/')
+                    .split(|c: char| c.is_whitespace() || c == '/')
+                    .next()
+                    .unwrap_or_default()
+                    .to_lowercase();
+                if matches!(name.as_str(), "br" | "div" | "p" | "li") && !text.is_empty() && !text.ends_with('\n') {
+                    text.push('\n');
+                }
+                tag.clear();
+            } else {
+                tag.push(c);
+            }
+        } else if c == '<' {
+            in_tag = true;
+        } else {
+            text.push(c);
+        }
+    }
+
+    text.replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+        .trim()
+        .to_string()
+}
+
+const SCHEMA: &str = "
+CREATE TABLE col (
+    id integer primary key, crt integer not null, mod integer not null, scm integer not null,
+    ver integer not null, dty integer not null, usn integer not null, ls integer not null,
+    conf text not null, models text not null, decks text not null, dconf text not null, tags text not null
+);
+CREATE TABLE notes (
+    id integer primary key, guid text not null, mid integer not null, mod integer not null,
+    usn integer not null, tags text not null, flds text not null, sfld integer not null,
+    csum integer not null, flags integer not null, data text not null
+);
+CREATE TABLE cards (
+    id integer primary key, nid integer not null, did integer not null, ord integer not null,
+    mod integer not null, usn integer not null, type integer not null, queue integer not null,
+    due integer not null, ivl integer not null, factor integer not null, reps integer not null,
+    lapses integer not null, left integer not null, odue integer not null, odid integer not null,
+    flags integer not null, data text not null
+);
+CREATE TABLE revlog (
+    id integer primary key, cid integer not null, usn integer not null, ease integer not null,
+    ivl integer not null, lastIvl integer not null, factor integer not null, time integer not null,
+    type integer not null
+);
+CREATE TABLE graves (usn integer not null, oid integer not null, type integer not null);
+CREATE INDEX ix_notes_usn ON notes (usn);
+CREATE INDEX ix_cards_usn ON cards (usn);
+CREATE INDEX ix_revlog_usn ON revlog (usn);
+CREATE INDEX ix_cards_nid ON cards (nid);
+CREATE INDEX ix_cards_sched ON cards (did, queue, due);
+CREATE INDEX ix_revlog_cid ON revlog (cid);
+CREATE INDEX ix_notes_csum ON notes (csum);
+";
+
+const NOTE_FIELDS: [&str; 5] = ["Front", "Back", "Examples", "Explanation", "Notes"];
+const NOTE_TYPE_ID: i64 = 1_700_000_000_001;
+const REVERSIBLE_NOTE_TYPE_ID: i64 = 1_700_000_000_002;
+const DEFAULT_DECK_ID: i64 = 1;
+
+const CARD_CSS: &str = ".card { font-family: arial; font-size: 20px; text-align: center; }";
+const ANSWER_EXTRAS: &str = "{{#Examples}}<br><br>{{Examples}}{{/Examples}}\
+    {{#Explanation}}<br><br>{{Explanation}}{{/Explanation}}\
+    {{#Notes}}<br><br><i>{{Notes}}</i>{{/Notes}}";
+
+pub fn export_anki(decks: &[&Deck], output: &Path) -> Result<()> {
+    let collection_path = std::env::temp_dir().join(format!("kkameokda-export-{}.sqlite", std::process::id()));
+    if collection_path.exists() {
+        fs::remove_file(&collection_path)?;
+    }
+
+    let result = Connection::open(&collection_path)
+        .map_err(Into::into)
+        .and_then(|conn| write_collection(&conn, decks));
+    let contents = fs::read(&collection_path);
+    fs::remove_file(&collection_path)?;
+    let exported = result?;
+
+    let mut archive = zip::ZipWriter::new(File::create(output)?);
+    let options = zip::write::SimpleFileOptions::default();
+    archive.start_file("collection.anki2", options)?;
+    archive.write_all(&contents?)?;
+    archive.start_file("media", options)?;
+    archive.write_all(b"{}")?;
+    archive.finish()?;
+
+    println!("Exported {} cards to {:?}", exported, output);
+    Ok(())
+}
+
+fn write_collection(conn: &Connection, decks: &[&Deck]) -> Result<usize> {
+    let now = current_unix_time() as i64;
+    conn.execute_batch(SCHEMA)?;
+
+    let mut deck_json = serde_json::Map::new();
+    deck_json.insert(DEFAULT_DECK_ID.to_string(), deck_entry(DEFAULT_DECK_ID, "Default", now));
+
+    let tx = conn.unchecked_transaction()?;
+    let mut next_id = now * 1000;
+    let mut exported = 0;
+
+    for deck in decks.iter().flat_map(|deck| deck.flatten()) {
+        // Anki uses `::` between deck levels where we use directories
+        let anki_name = deck.name.replace('/', "::");
+        let deck_id = (stable_hash(&anki_name) & ((1 << 53) - 1)) as i64;
+        deck_json.insert(deck_id.to_string(), deck_entry(deck_id, &anki_name, now));
+
+        for card in &deck.cards {
+            let note_id = next_id;
+            next_id += 1;
+            let note_type_id = if card.reversible { REVERSIBLE_NOTE_TYPE_ID } else { NOTE_TYPE_ID };
+            let fields = note_fields(card);
+            let sort_field = strip_html(&fields[0]);
+            let checksum = Sha1::digest(sort_field.as_bytes());
+            let checksum = u32::from_be_bytes([checksum[0], checksum[1], checksum[2], checksum[3]]);
+
+            tx.execute(
+                "INSERT INTO notes VALUES (?1, ?2, ?3, ?4, -1, ?5, ?6, ?7, ?8, 0, '')",
+                rusqlite::params![
+                    note_id,
+                    format!("{:x}", stable_hash(&card_key(card))),
+                    note_type_id,
+                    now,
+                    note_tags(card),
+                    fields.join(&FIELD_SEPARATOR.to_string()),
+                    sort_field,
+                    checksum,
+                ],
+            )?;
+
+            let templates = if card.reversible { 2 } else { 1 };
+            for ord in 0..templates {
+                tx.execute(
+                    "INSERT INTO cards VALUES (?1, ?2, ?3, ?4, ?5, -1, 0, 0, ?6, 0, 0, 0, 0, 0, 0, 0, 0, '')",
+                    rusqlite::params![next_id, note_id, deck_id, ord, now, exported as i64 + 1],
+                )?;
+                next_id += 1;
+            }
+            exported += 1;
+        }
+    }
+    tx.commit()?;
+
+    let models = serde_json::json!({
+        NOTE_TYPE_ID.to_string(): note_type(NOTE_TYPE_ID, "kkameokda", false, now),
+        REVERSIBLE_NOTE_TYPE_ID.to_string(): note_type(REVERSIBLE_NOTE_TYPE_ID, "kkameokda (reversible)", true, now),
+    });
+    let conf = serde_json::json!({
+        "activeDecks": [DEFAULT_DECK_ID],
+        "curDeck": DEFAULT_DECK_ID,
+        "newSpread": 0,
+        "collapseTime": 1200,
+        "timeLim": 0,
+        "estTimes": true,
+        "dueCounts": true,
+        "curModel": NOTE_TYPE_ID.to_string(),
+        "nextPos": exported + 1,
+        "sortType": "noteFld",
+        "sortBackwards": false,
+        "addToCur": true,
+    });
+    let dconf = serde_json::json!({ "1": deck_config(now) });
+
+    conn.execute(
+        "INSERT INTO col VALUES (1, ?1, ?2, ?3, 11, 0, 0, 0, ?4, ?5, ?6, ?7, '{}')",
+        rusqlite::params![
+            now - now % DAY as i64,
+            now * 1000,
+            now * 1000,
+            conf.to_string(),
+            models.to_string(),
+            serde_json::Value::Object(deck_json).to_string(),
+            dconf.to_string(),
+        ],
+    )?;
+
+    Ok(exported)
+}
+
+// Anki fields are HTML, with examples listed one per line
+// Anki keeps tags space-separated, with a space on either side
+fn note_tags(card: &Card) -> String {
+    if card.tags.is_empty() {
+        return String::new();
+    }
+    format!(" {} ", card.tags.iter().map(|tag| tag.replace(' ', "_")).collect::<Vec<_>>().join(" "))
+}
+
+fn note_fields(card: &Card) -> Vec<String> {
+    let html = |text: &str| escape_html(text).replace('\n', "<br>");
+    let examples = card.examples.as_deref()
+        .unwrap_or_default()
+        .iter()
+        .map(|example| format!("{} - {}", html(&example.sentence), html(&example.translation)))
+        .collect::<Vec<_>>()
+        .join("<br>");
+
+    vec![
+        html(&card.front),
+        html(&card.back),
+        examples,
+        card.explanation.as_deref().map(html).unwrap_or_default(),
+        card.notes.as_deref().map(html).unwrap_or_default(),
+    ]
+}
+
+fn note_type(id: i64, name: &str, reversible: bool, now: i64) -> serde_json::Value {
+    let template = |name: &str, ord: usize, question: &str, answer: &str| serde_json::json!({
+        "name": name,
+        "ord": ord,
+        "qfmt": format!("{{{{{}}}}}", question),
+        "afmt": format!("{{{{FrontSide}}}}<hr id=answer>{{{{{}}}}}{}", answer, ANSWER_EXTRAS),
+        "bqfmt": "",
+        "bafmt": "",
+        "did": null,
+        "bfont": "",
+        "bsize": 0,
+    });
+
+    let mut templates = vec![template("Card 1", 0, "Front", "Back")];
+    let mut requirements = vec![serde_json::json!([0, "any", [0]])];
+    if reversible {
+        templates.push(template("Card 2", 1, "Back", "Front"));
+        requirements.push(serde_json::json!([1, "any", [1]]));
+    }
+
+    let fields: Vec<serde_json::Value> = NOTE_FIELDS.iter()
+        .enumerate()
+        .map(|(ord, name)| serde_json::json!({
+            "name": name,
+            "ord": ord,
+            "sticky": false,
+            "rtl": false,
+            "font": "Arial",
+            "size": 20,
+            "media": [],
+        }))
+        .collect();
+
+    serde_json::json!({
+        "id": id,
+        "name": name,
+        "type": 0,
+        "mod": now,
+        "usn": -1,
+        "sortf": 0,
+        "did": DEFAULT_DECK_ID,
+        "tmpls": templates,
+        "flds": fields,
+        "css": CARD_CSS,
+        "latexPre": "\\documentclass[12pt]{article}\n\\special{papersize=3in,5in}\n\\usepackage{amssymb,amsmath}\n\\pagestyle{empty}\n\\setlength{\\parindent}{0in}\n\\begin{document}\n",
+        "latexPost": "\\end{document}",
+        "latexsvg": false,
+        "req": requirements,
+        "tags": [],
+        "vers": [],
+    })
+}
+
+fn deck_entry(id: i64, name: &str, now: i64) -> serde_json::Value {
+    serde_json::json!({
+        "id": id,
+        "name": name,
+        "mod": now,
+        "usn": -1,
+        "desc": "",
+        "dyn": 0,
+        "conf": 1,
+        "collapsed": false,
+        "browserCollapsed": false,
+        "extendNew": 10,
+        "extendRev": 50,
+        "newToday": [0, 0],
+        "revToday": [0, 0],
+        "lrnToday": [0, 0],
+        "timeToday": [0, 0],
+    })
+}
+
+fn deck_config(now: i64) -> serde_json::Value {
+    serde_json::json!({
+        "id": 1,
+        "name": "Default",
+        "mod": now,
+        "usn": -1,
+        "maxTaken": 60,
+        "autoplay": true,
+        "timer": 0,
+        "replayq": true,
+        "dyn": false,
+        "new": {
+            "delays": [1, 10],
+            "ints": [1, 4, 7],
+            "initialFactor": 2500,
+            "order": 1,
+            "perDay": 20,
+            "bury": false,
+        },
+        "rev": {
+            "perDay": 200,
+            "ease4": 1.3,
+            "ivlFct": 1,
+            "maxIvl": 36500,
+            "hardFactor": 1.2,
+            "bury": false,
+        },
+        "lapse": {
+            "delays": [10],
+            "mult": 0,
+            "minInt": 1,
+            "leechFails": 8,
+            "leechAction": 1,
+        },
+    })
+}
@@ -0,0 +1,114 @@
+// answer.rs
+use crate::hangul::jamo;
+
+/// Answers at least this similar to the expected one get partial credit.
+pub const PARTIAL_CREDIT_THRESHOLD: f64 = 0.75;
+
+pub enum AnswerCheck {
+    Correct,
+    /// Close to the answer, with how similar it was between 0 and 1
+    Close(f64),
+    Wrong,
+}
+
+/// Compares a typed answer with the side of the card being recalled. Backs
+/// like "hi/bye" accept any of their alternatives. Hangul is compared jamo by
+/// jamo, so a wrong final consonant only costs one letter of the syllable.
+pub fn check_answer(typed: &str, expected: &str) -> AnswerCheck {
+    let typed = normalize(typed);
+    if typed.is_empty() {
+        return AnswerCheck::Wrong;
+    }
+
+    let mut alternatives = vec![normalize(expected)];
+    alternatives.extend(expected.split(['/', ',', ';']).map(normalize));
+    alternatives.retain(|alternative| !alternative.is_empty());
+
+    if alternatives.contains(&typed) {
+        return AnswerCheck::Correct;
+    }
+
+    let best = alternatives.iter()
+        .map(|alternative| similarity(&typed, alternative))
+        .fold(0.0, f64::max);
+    if best >= PARTIAL_CREDIT_THRESHOLD {
+        AnswerCheck::Close(best)
+    } else {
+        AnswerCheck::Wrong
+    }
+}
+
+// Case, surrounding punctuation and repeated spaces don't make an answer wrong
+fn normalize(text: &str) -> String {
+    text.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .trim_matches(|c: char| c.is_ascii_punctuation() || c == '。' || c == '、')
+        .to_lowercase()
+}
+
+fn similarity(a: &str, b: &str) -> f64 {
+    let a = jamo(a);
+    let b = jamo(b);
+    let longest = a.len().max(b.len());
+    if longest == 0 {
+        return 1.0;
+    }
+    1.0 - edit_distance(&a, &b) as f64 / longest as f64
+}
+
+fn edit_distance(a: &[char], b: &[char]) -> usize {
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, a_char) in a.iter().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != b_char);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn is_correct(typed: &str, expected: &str) -> bool {
+        matches!(check_answer(typed, expected), AnswerCheck::Correct)
+    }
+
+    #[test]
+    fn any_alternative_is_correct() {
+        assert!(is_correct("bye", "hi/bye"));
+        assert!(is_correct("hi/bye", "hi/bye"));
+        assert!(is_correct("ship", "pear, ship"));
+        assert!(is_correct("belly", "pear; ship; belly"));
+        assert!(!is_correct("pear ship", "pear; ship"));
+    }
+
+    #[test]
+    fn case_spacing_and_surrounding_punctuation_are_ignored() {
+        assert!(is_correct("Hello!", "hello."));
+        assert!(is_correct("  good   morning ", "Good morning"));
+        assert!(is_correct("안녕하세요", "안녕하세요。"));
+        assert!(!is_correct("don't", "dont"));
+    }
+
+    #[test]
+    fn hangul_near_misses_are_close() {
+        // One vowel off is one of five letters, where it would be one of two syllables
+        let AnswerCheck::Close(similarity) = check_answer("핵교", "학교") else {
+            panic!("학교 typed as 핵교 should be close");
+        };
+        assert!(similarity >= PARTIAL_CREDIT_THRESHOLD);
+        assert!(matches!(check_answer("사과", "학교"), AnswerCheck::Wrong));
+    }
+
+    #[test]
+    fn empty_answers_are_wrong() {
+        assert!(matches!(check_answer("", "hi"), AnswerCheck::Wrong));
+        assert!(matches!(check_answer("   ", "hi"), AnswerCheck::Wrong));
+        assert!(matches!(check_answer("?", "hi"), AnswerCheck::Wrong));
+    }
+}
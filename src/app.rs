@@ -1,38 +1,87 @@
 // app.rs
+use std::fs;
+use std::path::{Path, PathBuf};
+use crate::answer::{check_answer, AnswerCheck};
+use crate::audio::Player;
+use crate::browser::{Browser, BrowserAction};
+use crate::card_form::{CardForm, FormAction};
+use crate::config::Config;
+use crate::images::{create_picker, load_card_image, CardImage};
 use crate::model::Card;
 use ratatui::prelude::*;
 use color_eyre::Result;
-use ratatui::crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+use ratatui::crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers, MouseEvent, MouseEventKind};
 use crate::ui::draw_hint;
 use crate::ui::draw_full;
-use crate::utilities::current_unix_time;
+use crate::ui::draw_listening;
+use crate::ui::{draw_line_input, Answer, CardTitle, CardView, Panel, PanelArea, LINE_BREAK};
+use crate::input::TextInput;
+use crate::load::{get_flashcards_dir, load_cards_from_file, load_decks};
+use crate::utilities::{current_unix_time, format_date, relative_path, unique_card_path};
 use crate::ui::draw_frame;
-use crate::model::Deck;
+use crate::model::{CardField, CardLocation, Deck, Flag};
+use crate::query::Query;
+use crate::organize::{move_card, move_path};
+use crate::trash::trash_card;
 use clap::Parser;
 use crate::args::Cli;
-use opener;
 use rand::Rng;
+use ratatui_image::picker::Picker;
 
 pub enum CardState {
     Hint,
     Full,
 }
 
+// The current card being corrected one field at a time
+struct QuickEdit {
+    card: Card,
+    field: CardField,
+    input: TextInput,
+}
+
 pub struct App {
     pub decks: Vec<Deck>,
     pub state: CardState,
     pub remembered_count: u32,
     pub forgotten_count: u32,
     pub reversed: bool,
+    config: Config,
     due_cards: Vec<(Card, String)>,
     current_card: Option<(Card, String)>,
     show_shortcuts: bool,  // Add this
+    view: CardView,
+    // Panels as laid out in the last frame, used to scroll them
+    panel_areas: Vec<PanelArea>,
+    // Only created when some card has an image, since it queries the terminal
+    picker: Option<Picker>,
+    card_image: Option<CardImage>,
+    needs_clear: bool,
+    player: Player,
+    // Listening practice, where the front is played rather than shown
+    listen: bool,
+    // Shown at the bottom of the screen until the next key press
+    message: Option<String>,
+    // Answers are typed before the card is turned over
+    type_answers: bool,
+    answer_input: Option<TextInput>,
+    checked_answer: Option<(String, AnswerCheck)>,
+    browser: Option<Browser>,
+    card_form: Option<CardForm>,
+    quick_edit: Option<QuickEdit>,
+    note_input: Option<TextInput>,
+    // Card file to open in the editor, which needs the terminal to itself
+    edit_request: Option<PathBuf>,
+    // Cards to study in a custom session instead of the due ones
+    query: Option<Query>,
+    session_start: u64,
 }
 
 impl App {
-    pub fn new(decks: Vec<Deck>) -> Self {
+    pub fn new(decks: Vec<Deck>, config: Config, query: Option<Query>) -> Self {
         let mut app = Self {
             decks,
+            config,
             state: CardState::Hint,
             remembered_count: 0,
             forgotten_count: 0,
@@ -40,7 +89,33 @@ impl App {
             due_cards: Vec::new(),
             current_card: None,
             show_shortcuts: false,  // Add this
+            view: CardView::default(),
+            panel_areas: Vec::new(),
+            picker: None,
+            card_image: None,
+            needs_clear: false,
+            player: Player::default(),
+            listen: Cli::parse().listen,
+            message: None,
+            type_answers: Cli::parse().type_answers,
+            answer_input: None,
+            checked_answer: None,
+            browser: None,
+            card_form: None,
+            quick_edit: None,
+            note_input: None,
+            edit_request: None,
+            query,
+            session_start: current_unix_time(),
         };
+        app.view.romanize = app.config.romanization;
+        app.view.ruby = app.config.ruby;
+        let has_images = app.decks.iter()
+            .flat_map(|deck| deck.flatten())
+            .any(|deck| deck.cards.iter().any(|card| card.image.is_some()));
+        if has_images {
+            app.picker = Some(create_picker(&app.config));
+        }
         app.refresh_due_cards();
         if !app.due_cards.is_empty() {
             app.next_card();
@@ -52,53 +127,43 @@ impl App {
         let current_time = current_unix_time();
         let mut cards = Vec::new();
         
-        for deck in &self.decks {
+        for deck in self.decks.iter().flat_map(|deck| deck.flatten()) {
             cards.extend(
                 deck.cards.iter()
-                    .filter(|card| card.next_review < Some(current_time))
+                    .filter(|card| match &self.query {
+                        Some(query) => {
+                            !card.suspended && query.matches(card, &deck.name, current_time)
+                                && !self.remembered_this_session(card)
+                        }
+                        None => card.is_due(current_time),
+                    })
                     .map(|card| (card.clone(), deck.name.clone()))
             );
-            
-            for subdeck in &deck.subdecks {
-                cards.extend(
-                    subdeck.cards.iter()
-                        .filter(|card| card.next_review < Some(current_time))
-                        .map(|card| (card.clone(), subdeck.name.clone()))
-                );
-            }
         }
         
         self.due_cards = cards;
     }
 
+    // A custom session goes through each card until it is remembered once,
+    // while forgotten cards come back as they do when studying due cards
+    fn remembered_this_session(&self, card: &Card) -> bool {
+        card.history.as_deref()
+            .and_then(|history| history.last())
+            .is_some_and(|review| review.remembered && review.date >= self.session_start)
+    }
+
     pub fn due_cards_count(&self) -> usize {
         self.due_cards.len()
     }
 
     fn get_card_mut(&mut self, card_to_find: &Card) -> Option<(&mut Card, &str)> {
-        let current_time = current_unix_time();
-        
-        for deck in &mut self.decks {
-            for card in &mut deck.cards {
-                if card.next_review < Some(current_time) && 
-                   card.front == card_to_find.front && 
-                   card.back == card_to_find.back {
-                    return Some((card, &deck.name));
-                }
-            }
-            
-            for subdeck in &mut deck.subdecks {
-                for card in &mut subdeck.cards {
-                    if card.next_review < Some(current_time) && 
-                       card.front == card_to_find.front && 
-                       card.back == card_to_find.back {
-                        return Some((card, &subdeck.name));
-                    }
-                }
-            }
-        }
-        
-        None
+        let location = Deck::locate_cards(&self.decks)
+            .into_iter()
+            .find(|(_, card, _)| card.is_same_card(card_to_find))
+            .map(|(location, _, _)| location)?;
+        let deck = Deck::deck_at_mut(&mut self.decks, &location.deck)?;
+        let card = deck.cards.get_mut(location.index)?;
+        Some((card, &deck.name))
     }
 
     fn current_card(&self) -> Option<(&Card, &str)> {
@@ -106,24 +171,474 @@ impl App {
             .map(|(card, name)| (card, name.as_str()))
     }
 
+    /// Whether keys are going to the typed answer, rather than acting as shortcuts.
+    pub fn is_typing(&self) -> bool {
+        self.answer_input.is_some()
+            || self.card_form.is_some()
+            || self.quick_edit.is_some()
+            || self.note_input.is_some()
+            || self.browser.as_ref().is_some_and(|browser| browser.is_typing())
+    }
+
+    pub fn is_browsing(&self) -> bool {
+        self.browser.is_some()
+    }
+
+    pub fn open_browser(&mut self) {
+        self.browser = Some(Browser::new(&self.decks));
+    }
+
     pub fn handle_event(&mut self, event: Event) -> Result<()> {
+        if let (Event::Key(key), Some(browser)) = (&event, &mut self.browser) {
+            let action = browser.handle_key(*key);
+            return self.apply_browser_action(action);
+        }
+
+        if let (Event::Key(key), Some(form)) = (&event, &mut self.card_form) {
+            match form.handle_key(*key) {
+                FormAction::None => {}
+                FormAction::Cancel => self.card_form = None,
+                FormAction::Save(card, deck) => match self.add_card(*card, &deck) {
+                    Ok(()) => self.card_form = None,
+                    Err(e) => {
+                        if let Some(form) = &mut self.card_form {
+                            form.error = Some(e.to_string());
+                        }
+                    }
+                },
+            }
+            return Ok(());
+        }
+
+        if let (Event::Key(key), Some(edit)) = (&event, &mut self.quick_edit) {
+            match key.code {
+                KeyCode::Tab => {
+                    edit.card.set_field(edit.field, from_single_line(&edit.input.value));
+                    edit.field = edit.field.next();
+                    edit.input = TextInput::new(&to_single_line(edit.card.field(edit.field)));
+                }
+                KeyCode::Enter => {
+                    edit.card.set_field(edit.field, from_single_line(&edit.input.value));
+                    if let Some(edit) = self.quick_edit.take() {
+                        if let Err(e) = self.save_quick_edit(edit.card) {
+                            self.message = Some(e.to_string());
+                        }
+                    }
+                }
+                KeyCode::Esc => self.quick_edit = None,
+                _ => {
+                    edit.input.handle_key(*key);
+                }
+            }
+            return Ok(());
+        }
+
+        if let (Event::Key(key), Some(input)) = (&event, &mut self.note_input) {
+            match key.code {
+                KeyCode::Enter => {
+                    let text = input.value.trim().to_string();
+                    self.note_input = None;
+                    if !text.is_empty() {
+                        if let Err(e) = self.append_note(&text) {
+                            self.message = Some(e.to_string());
+                        }
+                    }
+                }
+                KeyCode::Esc => self.note_input = None,
+                _ => {
+                    input.handle_key(*key);
+                }
+            }
+            return Ok(());
+        }
+
+        if let (Event::Key(key), Some(input)) = (&event, &mut self.answer_input) {
+            match key.code {
+                KeyCode::Enter => self.submit_answer(),
+                // Gives up and shows the answer without grading
+                KeyCode::Esc => {
+                    self.answer_input = None;
+                    self.toggle_state();
+                }
+                _ => {
+                    input.handle_key(*key);
+                }
+            }
+            return Ok(());
+        }
+
         if let Event::Key(KeyEvent { code, modifiers, .. }) = event {
+            self.message = None;
             match code {
                 KeyCode::Char(' ') => self.toggle_state(),
-                KeyCode::Enter => self.review_card(true)?,
+                KeyCode::Enter => match &self.checked_answer {
+                    Some((_, AnswerCheck::Close(_))) => self.review_partial()?,
+                    Some((_, AnswerCheck::Wrong)) => self.review_card(false)?,
+                    _ => self.review_card(true)?,
+                },
+                KeyCode::Char('a') => self.card_form = Some(CardForm::new(&self.decks, self.current_deck_name())),
+                KeyCode::Char('b') => self.open_browser(),
+                KeyCode::Char('e') if !modifiers.contains(KeyModifiers::CONTROL) => self.start_quick_edit(),
+                KeyCode::Char('n') => self.start_note(),
+                KeyCode::Char('0') => self.set_flag(None),
+                KeyCode::Char(c @ '1'..='5') => self.set_flag(Some(Flag::ALL[c as usize - '1' as usize])),
+                KeyCode::Char('r') => self.view.romanize = !self.view.romanize,
+                KeyCode::Char('y') => self.view.ruby = self.view.ruby.next(),
                 KeyCode::Char('f') => self.review_card(false)?,
                 KeyCode::Char('?') => self.show_shortcuts = !self.show_shortcuts,
                 KeyCode::Esc => self.show_shortcuts = false, 
+                KeyCode::Left => self.view.code_scroll = self.view.code_scroll.saturating_sub(4),
+                KeyCode::Right => self.view.code_scroll = self.view.code_scroll.saturating_add(4),
+                KeyCode::Tab => self.focus_next_panel(),
+                KeyCode::Up => self.scroll_focused_panel(-1),
+                KeyCode::Down => self.scroll_focused_panel(1),
+                KeyCode::PageUp => self.scroll_focused_panel(-(self.focused_panel_height() as i32)),
+                KeyCode::PageDown => self.scroll_focused_panel(self.focused_panel_height() as i32),
+                KeyCode::Char('p') => self.play_front(),
+                KeyCode::Char('P') => self.speak_example(),
                 KeyCode::Char('e') if modifiers.contains(KeyModifiers::CONTROL) => {
-                    if let Some((card, _)) = &self.current_card {
-                        if let Some(path) = &card.file_path {
-                            opener::open(path)?;
-                        }
-                    }
+                    self.edit_request = self.current_card.as_ref().and_then(|(card, _)| card.file_path.clone());
                 },
                 _ => {}
             }
         }
+        if let Event::Mouse(MouseEvent { kind, column, row, .. }) = event {
+            let position = Position::new(column, row);
+            let panel = self.panel_areas.iter()
+                .find(|panel_area| panel_area.area.contains(position))
+                .map(|panel_area| panel_area.panel);
+            if let Some(panel) = panel {
+                match kind {
+                    MouseEventKind::ScrollUp => self.scroll_panel(panel, -3),
+                    MouseEventKind::ScrollDown => self.scroll_panel(panel, 3),
+                    _ => {}
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // Cycles through the explanation, examples and notes panels shown
+    fn focus_next_panel(&mut self) {
+        let info_panels: Vec<Panel> = self.panel_areas.iter()
+            .map(|panel_area| panel_area.panel)
+            .filter(|panel| !matches!(panel, Panel::Front | Panel::Back))
+            .collect();
+        let next = match self.view.focus.and_then(|focus| info_panels.iter().position(|&panel| panel == focus)) {
+            Some(index) => info_panels.get(index + 1).copied(),
+            None => info_panels.first().copied(),
+        };
+        self.view.focus = next;
+    }
+
+    // Without a selected panel, the keys scroll the first one that overflows
+    fn scroll_focused_panel(&mut self, lines: i32) {
+        let panel = self.view.focus.or_else(|| {
+            self.panel_areas.iter()
+                .find(|panel_area| panel_area.max_scroll > 0)
+                .map(|panel_area| panel_area.panel)
+        });
+        if let Some(panel) = panel {
+            self.scroll_panel(panel, lines);
+        }
+    }
+
+    fn focused_panel_height(&self) -> u16 {
+        self.panel_areas.iter()
+            .find(|panel_area| Some(panel_area.panel) == self.view.focus)
+            .map_or(10, |panel_area| panel_area.height.saturating_sub(1).max(1))
+    }
+
+    fn scroll_panel(&mut self, panel: Panel, lines: i32) {
+        let Some(max_scroll) = self.panel_areas.iter()
+            .find(|panel_area| panel_area.panel == panel)
+            .map(|panel_area| panel_area.max_scroll)
+        else {
+            return;
+        };
+        let scroll = (self.view.scroll(panel) as i32 + lines).clamp(0, max_scroll as i32);
+        self.view.set_scroll(panel, scroll as u16);
+    }
+
+    fn apply_browser_action(&mut self, action: BrowserAction) -> Result<()> {
+        let result = match action {
+            BrowserAction::None => Ok(()),
+            BrowserAction::Close => return self.close_browser(),
+            BrowserAction::Edit(location) => {
+                self.edit_request = Deck::card_at(&self.decks, &location).and_then(|card| card.file_path.clone());
+                Ok(())
+            }
+            BrowserAction::ToggleSuspend(location) => match Deck::card_at_mut(&mut self.decks, &location) {
+                Some(card) => {
+                    card.suspended = !card.suspended;
+                    card.save()
+                }
+                None => Ok(()),
+            },
+            BrowserAction::Reschedule(location, days) => match Deck::card_at_mut(&mut self.decks, &location) {
+                Some(card) => {
+                    let interval = days.checked_mul(24 * 60 * 60);
+                    match interval.and_then(|interval| current_unix_time().checked_add(interval).map(|due| (interval, due))) {
+                        Some((interval, due)) => {
+                            card.next_review = Some(due);
+                            if days > 0 {
+                                card.ease_factor = Some(interval);
+                            }
+                            card.save()
+                        }
+                        None => Err(color_eyre::eyre::eyre!("Too many days")),
+                    }
+                }
+                None => Ok(()),
+            },
+            BrowserAction::Delete(location) => self.delete_card(&location),
+            BrowserAction::Move(location, deck) => self.move_card(&location, &deck),
+            BrowserAction::RenameDeck(deck, name) => self.rename_deck(&deck, &name),
+        };
+
+        if let Some(browser) = &mut self.browser {
+            if let Err(e) = result {
+                browser.message = Some(e.to_string());
+            }
+            browser.refresh(&self.decks);
+        }
+        Ok(())
+    }
+
+    fn delete_card(&mut self, location: &CardLocation) -> Result<()> {
+        let Some(deck) = Deck::deck_at_mut(&mut self.decks, &location.deck) else {
+            return Ok(());
+        };
+        let Some(card) = deck.cards.get(location.index) else {
+            return Ok(());
+        };
+        let entry = trash_card(card)?;
+        let card = deck.cards.remove(location.index);
+
+        // Later cards from the same file moved up one place in it
+        if let Some(removed_index) = card.file_index {
+            for other in &mut deck.cards {
+                if other.file_path == card.file_path && other.file_index.is_some_and(|index| index > removed_index) {
+                    other.file_index = other.file_index.map(|index| index - 1);
+                }
+            }
+        }
+        self.show_message(format!("Moved to the trash, restore it with `kkameokda trash restore {}`", entry.id));
+        Ok(())
+    }
+
+    fn move_card(&mut self, location: &CardLocation, deck: &str) -> Result<()> {
+        let Some(card) = Deck::card_at(&self.decks, location) else {
+            return Ok(());
+        };
+        let target = move_card(card, &get_flashcards_dir().join(deck))?;
+        self.reload_decks()?;
+        self.show_message(format!("Moved to {}", relative_path(&target)));
+        Ok(())
+    }
+
+    fn rename_deck(&mut self, deck: &str, name: &str) -> Result<()> {
+        let flashcards_dir = get_flashcards_dir();
+        let target = flashcards_dir.join(name);
+        // Renaming never moves a deck into another existing one
+        if target.exists() {
+            return Err(color_eyre::eyre::eyre!("{} already exists", name));
+        }
+        move_path(&flashcards_dir.join(deck), &target)?;
+        self.reload_decks()?;
+        self.show_message(format!("Renamed {} to {}", deck, name));
+        Ok(())
+    }
+
+    // Loads every deck again after files moved, following the current card to its new place
+    fn reload_decks(&mut self) -> Result<()> {
+        self.decks = load_decks()?;
+        if let Some((current, deck_name)) = &mut self.current_card {
+            let moved = Deck::locate_cards(&self.decks)
+                .into_iter()
+                .find(|(_, card, _)| card.is_same_card(current))
+                .or_else(|| Deck::locate_cards(&self.decks)
+                    .into_iter()
+                    .find(|(_, card, _)| card.front == current.front && card.back == current.back));
+            match moved {
+                Some((_, card, deck)) => {
+                    *current = card.clone();
+                    *deck_name = deck.name.clone();
+                }
+                None => self.current_card = None,
+            }
+        }
+        self.refresh_due_cards();
+        Ok(())
+    }
+
+    fn start_quick_edit(&mut self) {
+        if !matches!(self.state, CardState::Full) {
+            return;
+        }
+        let Some((card, _)) = &self.current_card else {
+            return;
+        };
+        if card.inline_id.is_some() {
+            self.message = Some("Cards inside notes are edited in the note, with Ctrl+e".to_string());
+            return;
+        }
+        self.quick_edit = Some(QuickEdit {
+            card: card.clone(),
+            field: CardField::Front,
+            input: TextInput::new(&to_single_line(&card.front)),
+        });
+    }
+
+    // Shows the saved copy of the current card after it was changed
+    fn replace_current_card(&mut self, card: Card) {
+        if let Some((current, _)) = &mut self.current_card {
+            *current = card;
+        }
+        self.refresh_due_cards();
+    }
+
+    // Flags the current card, or clears its flag when given the one it already has
+    fn set_flag(&mut self, flag: Option<Flag>) {
+        let Some((current, _)) = &self.current_card else {
+            return;
+        };
+        let current = current.clone();
+        let Some((card, _)) = self.get_card_mut(&current) else {
+            return;
+        };
+        card.flag = if card.flag == flag { None } else { flag };
+        if let Err(e) = card.save() {
+            self.message = Some(e.to_string());
+            return;
+        }
+        let card = card.clone();
+        self.replace_current_card(card);
+    }
+
+    fn start_note(&mut self) {
+        match &self.current_card {
+            Some((card, _)) if card.inline_id.is_some() => {
+                self.message = Some("Cards inside notes are edited in the note, with Ctrl+e".to_string());
+            }
+            Some(_) => self.note_input = Some(TextInput::default()),
+            None => {}
+        }
+    }
+
+    // Adds a line to the end of the current card's notes and saves it
+    fn append_note(&mut self, text: &str) -> Result<()> {
+        let line = if self.config.timestamp_notes {
+            format!("{}: {}", format_date(current_unix_time()), text)
+        } else {
+            text.to_string()
+        };
+        let Some((current, _)) = &self.current_card else {
+            return Ok(());
+        };
+        let current = current.clone();
+        let Some((card, _)) = self.get_card_mut(&current) else {
+            return Ok(());
+        };
+        card.notes = Some(match card.notes.as_deref().map(str::trim_end) {
+            Some(notes) if !notes.is_empty() => format!("{}\n{}", notes, line),
+            _ => line,
+        });
+        card.save()?;
+
+        let card = card.clone();
+        self.replace_current_card(card);
+        if matches!(self.state, CardState::Hint) {
+            self.message = Some("Added to the notes".to_string());
+        }
+        Ok(())
+    }
+
+    // Writes the edited fields to the card's file, leaving its review state as it is
+    fn save_quick_edit(&mut self, edited: Card) -> Result<()> {
+        if edited.front.trim().is_empty() || edited.back.trim().is_empty() {
+            return Err(color_eyre::eyre::eyre!("A card needs both a front and a back"));
+        }
+        let Some((card, _)) = self.get_card_mut(&edited) else {
+            return Ok(());
+        };
+        for field in [CardField::Front, CardField::Back, CardField::Notes, CardField::Explanation] {
+            card.set_field(field, edited.field(field).to_string());
+        }
+        card.save()?;
+
+        let card = card.clone();
+        self.replace_current_card(card);
+        Ok(())
+    }
+
+    /// Writes a new card into the directory of the deck at `deck_path`, where
+    /// it can be studied straight away.
+    fn add_card(&mut self, mut card: Card, deck_path: &[usize]) -> Result<()> {
+        let Some(deck) = Deck::deck_at_mut(&mut self.decks, deck_path) else {
+            return Ok(());
+        };
+        let path = unique_card_path(&get_flashcards_dir().join(&deck.name), &card.front, "yaml");
+        card.added = Some(current_unix_time());
+        fs::write(&path, serde_yaml::to_string(&card)?)?;
+
+        card.file_path = Some(path);
+        card.initialize_review_data();
+        deck.cards.push(card);
+        self.message = Some(format!("Added a card to {}", deck.name));
+
+        self.refresh_due_cards();
+        if self.current_card.is_none() {
+            self.next_card();
+        }
+        Ok(())
+    }
+
+    // Back to reviewing, with cards that are still due after any changes
+    fn close_browser(&mut self) -> Result<()> {
+        self.browser = None;
+        self.refresh_due_cards();
+        if self.due_cards_count() == 0 {
+            return Err(color_eyre::eyre::eyre!("No more cards due for review"));
+        }
+        let still_due = self.current_card.as_ref().is_some_and(|(current, _)| {
+            self.due_cards.iter().any(|(card, _)| card.front == current.front && card.back == current.back)
+        });
+        if !still_due {
+            self.next_card();
+        }
+        Ok(())
+    }
+
+    fn submit_answer(&mut self) {
+        let (Some(input), Some((card, _))) = (self.answer_input.take(), &self.current_card) else {
+            return;
+        };
+        let expected = if self.reversed { &card.front } else { &card.back };
+        let check = check_answer(&input.value, expected);
+        self.checked_answer = Some((input.value, check));
+        self.toggle_state();
+    }
+
+    // A nearly right typed answer counts as remembered, with a shorter interval
+    fn review_partial(&mut self) -> Result<()> {
+        let current_time = current_unix_time();
+        self.remembered_count += 1;
+
+        let current_card = match &self.current_card {
+            Some((card, _)) => card.clone(),
+            None => return Ok(()),
+        };
+        let reversed = self.reversed;
+        if let Some((card, _)) = self.get_card_mut(&current_card) {
+            card.calculate_partial_review(current_time, reversed)?;
+        }
+
+        self.refresh_due_cards();
+        if self.due_cards_count() == 0 {
+            return Err(color_eyre::eyre::eyre!("No more cards due for review"));
+        }
+        self.next_card();
         Ok(())
     }
 
@@ -143,8 +658,9 @@ impl App {
         };
 
         // Now we can mutably borrow self
+        let reversed = self.reversed;
         if let Some((card, _)) = self.get_card_mut(&current_card) {
-            card.calculate_next_review(current_time, remembered)?;
+            card.calculate_next_review(current_time, remembered, reversed)?;
         }
 
         self.refresh_due_cards();
@@ -158,11 +674,129 @@ impl App {
         Ok(())
     }
 
+    /// Whether the screen has to be cleared before the next frame, because
+    /// terminal graphics from a previous image may still be showing.
+    pub fn take_needs_clear(&mut self) -> bool {
+        std::mem::take(&mut self.needs_clear)
+    }
+
+    pub fn take_edit_request(&mut self) -> Option<PathBuf> {
+        self.edit_request.take()
+    }
+
+    /// Shows `message` in the browser if it is open, or under the card.
+    pub fn show_message(&mut self, message: String) {
+        match &mut self.browser {
+            Some(browser) => browser.message = Some(message),
+            None => self.message = Some(message),
+        }
+    }
+
+    /// Reads the cards in `path` again after it was edited, replacing the ones
+    /// loaded from it before. If the file no longer parses, the old cards are
+    /// kept and the error is shown.
+    pub fn reload_file(&mut self, path: &Path) {
+        // The editor drew over any image
+        self.needs_clear = true;
+        let mut cards = match load_cards_from_file(path) {
+            Ok(cards) => cards,
+            Err(e) => {
+                self.show_message(format!("Could not read {}: {}", relative_path(path), e));
+                return;
+            }
+        };
+        for card in &mut cards {
+            card.initialize_review_data();
+        }
+
+        let location = Deck::locate_cards(&self.decks)
+            .into_iter()
+            .find(|(_, card, _)| card.file_path.as_deref() == Some(path))
+            .map(|(location, _, _)| location);
+        let Some(deck) = location.as_ref().and_then(|location| Deck::deck_at_mut(&mut self.decks, &location.deck)) else {
+            return;
+        };
+        let index = location.map(|location| location.index).unwrap_or_default();
+        deck.cards.retain(|card| card.file_path.as_deref() != Some(path));
+        deck.cards.splice(index..index, cards);
+
+        // Keep showing the edited card, unless it was removed from the file
+        let deck_name = deck.name.clone();
+        let edited = self.current_card.as_ref()
+            .filter(|(current, _)| current.file_path.as_deref() == Some(path))
+            .map(|(current, _)| {
+                deck.cards.iter()
+                    .find(|card| {
+                        card.file_path.as_deref() == Some(path)
+                            && card.file_index == current.file_index
+                            && card.inline_id == current.inline_id
+                    })
+                    .cloned()
+            });
+        self.refresh_due_cards();
+        match edited {
+            Some(Some(card)) => self.current_card = Some((card, deck_name)),
+            Some(None) => self.next_card(),
+            None => {}
+        }
+        if let Some(browser) = &mut self.browser {
+            browser.refresh(&self.decks);
+        }
+    }
+
+    fn current_image_path(&self) -> Option<std::path::PathBuf> {
+        self.current_card().and_then(|(card, _)| card.image_path())
+    }
+
     pub fn toggle_state(&mut self) {
+        self.needs_clear |= self.card_image.is_some();
         self.state = match self.state {
             CardState::Hint => CardState::Full,
             CardState::Full => CardState::Hint,
         };
+        // Reversed cards hold back their audio until the answer is revealed
+        if matches!(self.state, CardState::Full) && self.reversed && self.config.autoplay {
+            self.play_front();
+        }
+    }
+
+    // Plays the card's audio, or speaks its front when it has none
+    fn play_front(&mut self) {
+        let Some((card, _)) = &self.current_card else {
+            return;
+        };
+        // The front is the answer of a reversed card
+        if self.reversed && matches!(self.state, CardState::Hint) {
+            self.message = Some("The audio plays once the answer is shown".to_string());
+            return;
+        }
+        let result = match (card.audio_path(), &self.config.tts_command) {
+            (Some(path), _) => self.player.play_file(&self.config.audio_command, &path),
+            (None, Some(tts_command)) => self.player.speak(tts_command, &card.front),
+            (None, None) => Err(color_eyre::eyre::eyre!("This card has no audio")),
+        };
+        if let Err(e) = result {
+            self.message = Some(e.to_string());
+        }
+    }
+
+    fn speak_example(&mut self) {
+        let Some((card, _)) = &self.current_card else {
+            return;
+        };
+        let sentence = card.examples.as_ref().and_then(|examples| examples.first());
+        let result = match (sentence, &self.config.tts_command) {
+            (Some(example), Some(tts_command)) => self.player.speak(tts_command, &example.sentence),
+            (None, _) => Err(color_eyre::eyre::eyre!("This card has no example sentences")),
+            (_, None) => Err(color_eyre::eyre::eyre!("Set tts_command in the config to hear examples")),
+        };
+        if let Err(e) = result {
+            self.message = Some(e.to_string());
+        }
+    }
+
+    fn can_play(&self, card: &Card) -> bool {
+        card.audio.is_some() || self.config.tts_command.is_some()
     }
 
     fn next_card(&mut self) {
@@ -172,13 +806,20 @@ impl App {
             let next_card = self.due_cards.get(index).cloned();
             
             if let Some((card, deck_name)) = next_card {
+                self.needs_clear |= self.card_image.is_some() || card.image.is_some();
                 self.current_card = Some((card.clone(), deck_name));
                 self.state = CardState::Hint;
-                self.reversed = if card.reversible && cli.reversible.unwrap_or(true) {
+                self.view = self.view.for_next_card();
+                self.answer_input = self.type_answers.then(TextInput::default);
+                self.checked_answer = None;
+                self.reversed = if card.reversible && cli.reversible.unwrap_or(true) && !self.listen {
                     rand::random()
                 } else {
                     false
                 };
+                if (self.listen || self.config.autoplay) && !self.reversed && self.can_play(&card) {
+                    self.play_front();
+                }
             }
         }
     }
@@ -187,16 +828,61 @@ impl App {
         self.current_card().map(|(_, deck_path)| deck_path)
     }
 
-    pub fn draw(&self, f: &mut Frame) {
+    pub fn draw(&mut self, f: &mut Frame) {
+        if let Some(browser) = &mut self.browser {
+            browser.draw(f, &self.decks, &self.config);
+            return;
+        }
         let total_due = self.due_cards_count();
 
-        if let Some((card, _)) = self.current_card() {
-            match self.state {
-                CardState::Hint => draw_hint(f, card, self.reversed),
-                CardState::Full => draw_full(f, card, self.reversed),
-            }
+        let image_path = self.current_image_path();
+        if self.card_image.as_ref().map(|image| &image.path) != image_path.as_ref() {
+            self.card_image = match (&mut self.picker, image_path) {
+                (Some(picker), Some(path)) => Some(load_card_image(picker, &path)),
+                _ => None,
+            };
+        }
+
+        if let Some((card, _)) = &self.current_card {
+            let listening = self.listen && self.can_play(card);
+            let image = self.card_image.as_mut();
+            let answer = match (&self.answer_input, &self.checked_answer) {
+                (Some(input), _) => Some(Answer::Typing(input)),
+                (None, Some((typed, check))) => Some(Answer::Checked(typed, check)),
+                (None, None) => None,
+            };
+            self.panel_areas = match self.state {
+                // Nothing is shown while listening, so the card is recalled by ear
+                CardState::Hint if listening => {
+                    draw_listening(f, answer);
+                    Vec::new()
+                }
+                CardState::Hint => draw_hint(f, card, self.reversed, &self.config, &self.view, image, answer),
+                CardState::Full => draw_full(f, card, self.reversed, &self.config, &self.view, image, answer),
+            };
+        }
+        let title = self.current_card().map(|(card, deck)| CardTitle {
+            deck,
+            flag: card.flag.map(|flag| (flag, self.config.flag_label(flag))),
+        });
+        draw_frame(f, total_due, self.remembered_count, self.forgotten_count, title, self.show_shortcuts, self.message.as_deref());
+        if let Some(form) = &self.card_form {
+            form.draw(f);
+        }
+        if let Some(edit) = &self.quick_edit {
+            let title = format!("Edit {}", edit.field.name());
+            draw_line_input(f, &title, "Tab: next field │ Enter: save │ Esc: cancel", &edit.input);
+        }
+        if let Some(input) = &self.note_input {
+            draw_line_input(f, "Add to notes", "Enter: add │ Esc: cancel", input);
         }
-        draw_frame(f, total_due, self.remembered_count, self.forgotten_count, self.current_deck_name(), self.show_shortcuts);
 
     }
-}
\ No newline at end of file
+}
+fn to_single_line(text: &str) -> String {
+    text.replace('\n', &LINE_BREAK.to_string())
+}
+
+fn from_single_line(text: &str) -> String {
+    text.replace(LINE_BREAK, "\n")
+}
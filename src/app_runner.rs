@@ -1,26 +1,56 @@
 // app_runner.rs
+use std::io::stdout;
 use ratatui::{
-    crossterm::event::{self, Event, KeyCode},
+    crossterm::event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode},
+    crossterm::execute,
     DefaultTerminal,
 };
+use crate::editor::edit_file;
+use crate::config::Config;
+use crate::query::Query;
 use crate::model::Deck;
 use crate::app::App;
 use color_eyre::Result;
 
-pub fn run(mut terminal: DefaultTerminal, decks: Vec<Deck>) -> Result<App> {
-    let mut app = App::new(decks);
+pub fn run(mut terminal: DefaultTerminal, decks: Vec<Deck>, config: Config, browse: bool, query: Option<Query>) -> Result<App> {
+    let custom_session = query.is_some();
+    let mut app = App::new(decks, config, query);
+    if browse {
+        app.open_browser();
+    }
 
-    if app.due_cards_count() == 0 {
+    if app.due_cards_count() == 0 && !app.is_browsing() {
+        if custom_session {
+            return Err(color_eyre::eyre::eyre!("No cards match the query"));
+        }
         return Err(color_eyre::eyre::eyre!("No cards due for review"));
     }
 
     loop {
+        if let Some(path) = app.take_edit_request() {
+            // The editor runs in the foreground, with the terminal back to normal
+            execute!(stdout(), DisableMouseCapture)?;
+            ratatui::restore();
+            let edited = edit_file(&path);
+            terminal = ratatui::init();
+            execute!(stdout(), EnableMouseCapture)?;
+            match edited {
+                Ok(()) => app.reload_file(&path),
+                Err(e) => app.show_message(e.to_string()),
+            }
+        }
+        if app.take_needs_clear() {
+            terminal.clear()?;
+        }
         terminal.draw(|f| app.draw(f))?;
-        if let Event::Key(key) = event::read()? {
-            if key.code == KeyCode::Char('q') {
+        let event = event::read()?;
+        if let Event::Key(key) = &event {
+            if key.code == KeyCode::Char('q') && !app.is_typing() {
                 return Ok(app);
             }
-            if let Err(e) = app.handle_event(Event::Key(key)) {
+        }
+        if matches!(event, Event::Key(_) | Event::Mouse(_)) {
+            if let Err(e) = app.handle_event(event) {
                 if e.to_string() == "No more cards due for review" {
                     return Ok(app);
                 }
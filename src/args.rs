@@ -1,10 +1,192 @@
 // args.rs
-use clap::Parser;
+use std::path::PathBuf;
+use clap::{Parser, Subcommand, ValueEnum};
+use crate::model::Flag;
 
 #[derive(Parser)]
 pub struct Cli {
-    #[arg(short = 'd', long = "directory")]
+    #[arg(short = 'd', long = "directory", global = true)]
     pub directory: Option<String>,
     #[arg(short = 'r', long = "reversible")]
     pub reversible: Option<bool>,
-}
\ No newline at end of file
+    /// Listening practice: the front of each card is played instead of shown
+    #[arg(short = 'l', long = "listen")]
+    pub listen: bool,
+    /// Type each answer before the card is turned over
+    #[arg(short = 't', long = "type")]
+    pub type_answers: bool,
+    /// Review the cards matching a search query, whether or not they are due
+    #[arg(short = 'q', long = "query")]
+    pub query: Option<String>,
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Browse, search and edit every card
+    Browse,
+    /// List the cards matching a search query
+    List {
+        /// Search query, such as `deck:Korean tag:verb lapses>3`
+        #[arg(short = 'q', long = "query", default_value = "")]
+        query: String,
+        /// Only list flagged cards, or the cards with this flag
+        #[arg(long = "flagged", value_enum, num_args = 0..=1)]
+        flagged: Option<Option<Flag>>,
+    },
+    /// Move a card file or deck directory, e.g. `mv Korean/verbs.yaml Korean/grammar`
+    Mv {
+        /// Card file or deck, relative to the flashcards directory
+        source: PathBuf,
+        /// Deck to move it into, or its new path
+        destination: PathBuf,
+    },
+    /// Move card files or deck directories to the trash
+    Rm {
+        /// Card files or decks, relative to the flashcards directory
+        #[arg(required = true)]
+        paths: Vec<PathBuf>,
+    },
+    /// List, restore or empty the trash
+    Trash {
+        #[command(subcommand)]
+        action: Option<TrashAction>,
+    },
+    /// Find cards that duplicate each other across all decks
+    Dupes {
+        /// Go through each group of duplicates, merging it into one card
+        #[arg(long = "merge")]
+        merge: bool,
+    },
+    /// Export the review history of every card as an Anki-style revlog
+    ExportLog {
+        #[arg(short = 'f', long = "format", value_enum, default_value_t = LogFormat::Csv)]
+        format: LogFormat,
+        /// Write to this file instead of stdout
+        #[arg(short = 'o', long = "output")]
+        output: Option<String>,
+    },
+    /// Export decks for use in another application
+    Export {
+        #[command(subcommand)]
+        target: ExportTarget,
+    },
+    /// Import cards from another application
+    Import {
+        #[command(subcommand)]
+        source: ImportSource,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ExportTarget {
+    /// Export decks as an Anki package (.apkg)
+    Anki {
+        /// Only export this deck and its subdecks, e.g. Korean/grammar
+        #[arg(long = "deck")]
+        deck: Option<String>,
+        output: PathBuf,
+    },
+    /// Export every card as a row of a CSV or TSV table
+    Csv {
+        /// Only export this deck and its subdecks, e.g. Korean/grammar
+        #[arg(long = "deck")]
+        deck: Option<String>,
+        /// Column delimiter (defaults to tab for .tsv files and comma otherwise)
+        #[arg(long = "delimiter")]
+        delimiter: Option<char>,
+        /// Include when each card is due and how often it was reviewed
+        #[arg(long = "schedule")]
+        schedule: bool,
+        /// Write to this file instead of stdout
+        #[arg(short = 'o', long = "output")]
+        output: Option<PathBuf>,
+    },
+    /// Export decks as a Markdown study sheet
+    Markdown {
+        /// Only export this deck and its subdecks, e.g. Korean/grammar
+        #[arg(long = "deck")]
+        deck: Option<String>,
+        /// Include when each card is due and how often it was reviewed
+        #[arg(long = "schedule")]
+        schedule: bool,
+        /// Write to this file instead of stdout
+        #[arg(short = 'o', long = "output")]
+        output: Option<PathBuf>,
+    },
+    /// Export decks as a printable, self-contained HTML study sheet
+    Html {
+        /// Only export this deck and its subdecks, e.g. Korean/grammar
+        #[arg(long = "deck")]
+        deck: Option<String>,
+        /// Put fronts and backs in two columns so the page can be folded in half
+        #[arg(long = "fold")]
+        fold: bool,
+        /// Only include cards that are due or are leeches
+        #[arg(long = "only", value_enum)]
+        only: Option<CardFilter>,
+        output: PathBuf,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum TrashAction {
+    /// List what is in the trash (the default)
+    List,
+    /// Put something from the trash back where it was
+    Restore {
+        /// ID shown by `kkameokda trash`
+        id: String,
+    },
+    /// Delete everything in the trash for good
+    Empty,
+}
+
+#[derive(ValueEnum, Clone, Copy)]
+pub enum CardFilter {
+    Due,
+    Leech,
+}
+
+#[derive(Subcommand)]
+pub enum ImportSource {
+    /// Import an Anki package (.apkg) into matching deck directories
+    Anki {
+        file: PathBuf,
+        /// Carry over when each card is next due
+        #[arg(long = "scheduling")]
+        scheduling: bool,
+        /// Carry over the review history of each card
+        #[arg(long = "history")]
+        history: bool,
+    },
+    /// Import a CSV or TSV file, one card per row
+    Csv {
+        file: PathBuf,
+        /// Deck directory to add the cards to, e.g. Korean/vocab
+        #[arg(long = "deck")]
+        deck: String,
+        /// Columns for each field, by number or header name, e.g. front=1,back=2,notes=4
+        #[arg(long = "map")]
+        map: Option<String>,
+        /// Column delimiter (defaults to tab for .tsv files and comma otherwise)
+        #[arg(long = "delimiter")]
+        delimiter: Option<char>,
+        /// Treat the first row as column names
+        #[arg(long = "header")]
+        header: bool,
+        /// Write all cards into one file instead of a file per card
+        #[arg(long = "single-file")]
+        single_file: bool,
+        /// Show what would be imported without writing anything
+        #[arg(long = "dry-run")]
+        dry_run: bool,
+    },
+}
+
+#[derive(ValueEnum, Clone, Copy)]
+pub enum LogFormat {
+    Csv,
+    Json,
+}
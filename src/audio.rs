@@ -0,0 +1,77 @@
+// audio.rs
+use std::io::Write;
+use std::path::Path;
+use std::process::{Child, Command, Stdio};
+use color_eyre::{eyre::eyre, Result};
+
+/// Plays card audio and speech through external commands, one sound at a
+/// time: starting a new one stops whatever is still playing.
+#[derive(Default)]
+pub struct Player {
+    child: Option<Child>,
+}
+
+impl Player {
+    /// Plays `path` with `command`, which gets the file in place of `{file}`,
+    /// or as its last argument when there is no `{file}`.
+    pub fn play_file(&mut self, command: &str, path: &Path) -> Result<()> {
+        let file = path.to_string_lossy();
+        let mut words = command_words(command)?;
+        if words.iter().any(|word| word.contains("{file}")) {
+            for word in &mut words {
+                *word = word.replace("{file}", &file);
+            }
+        } else {
+            words.push(file.to_string());
+        }
+
+        self.stop();
+        self.child = Some(spawn(&words, Stdio::null())?);
+        Ok(())
+    }
+
+    /// Speaks `text` by writing it to the standard input of `command`.
+    pub fn speak(&mut self, command: &str, text: &str) -> Result<()> {
+        let words = command_words(command)?;
+
+        self.stop();
+        let mut child = spawn(&words, Stdio::piped())?;
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(text.as_bytes())?;
+        }
+        self.child = Some(child);
+        Ok(())
+    }
+
+    pub fn stop(&mut self) {
+        if let Some(mut child) = self.child.take() {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+    }
+}
+
+impl Drop for Player {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+fn command_words(command: &str) -> Result<Vec<String>> {
+    let words: Vec<String> = command.split_whitespace().map(String::from).collect();
+    if words.is_empty() {
+        return Err(eyre!("Audio command is empty"));
+    }
+    Ok(words)
+}
+
+// Output is discarded, since anything written to the terminal would garble the TUI
+fn spawn(words: &[String], stdin: Stdio) -> Result<Child> {
+    Command::new(&words[0])
+        .args(&words[1..])
+        .stdin(stdin)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| eyre!("Could not run {}: {}", words[0], e))
+}
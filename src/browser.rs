@@ -0,0 +1,446 @@
+// browser.rs
+use ratatui::crossterm::event::{KeyCode, KeyEvent};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::block::{Position, Title};
+use ratatui::widgets::{Block, Borders, Paragraph, Row, Table, TableState};
+use ratatui::Frame;
+use crate::config::Config;
+use crate::input::TextInput;
+use crate::model::{Card, CardLocation, Deck};
+use crate::query::{Query, QueryError};
+use crate::ui::{draw_full, flag_color, CardView};
+use crate::utilities::{current_unix_time, first_line, format_date, format_interval};
+
+/// What the browser asks the app to do with the deck tree, which it only reads.
+pub enum BrowserAction {
+    None,
+    Close,
+    Edit(CardLocation),
+    ToggleSuspend(CardLocation),
+    /// Make the card due in this many days
+    Reschedule(CardLocation, u64),
+    /// Move the card to the trash
+    Delete(CardLocation),
+    /// Move the card to the deck with this name
+    Move(CardLocation, String),
+    /// Rename the deck with the first name to the second
+    RenameDeck(String, String),
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Column {
+    Front,
+    Back,
+    Deck,
+    Due,
+    Interval,
+    Lapses,
+}
+
+const COLUMNS: [(Column, &str); 6] = [
+    (Column::Front, "Front"),
+    (Column::Back, "Back"),
+    (Column::Deck, "Deck"),
+    (Column::Due, "Due"),
+    (Column::Interval, "Interval"),
+    (Column::Lapses, "Lapses"),
+];
+
+enum Mode {
+    List,
+    Search,
+    Reschedule(TextInput),
+    ConfirmDelete,
+    /// Choosing a deck to move the card to, by its index in `decks`
+    Move(usize),
+    RenameDeck(TextInput),
+    Preview,
+}
+
+struct CardRow {
+    location: CardLocation,
+    card: Card,
+    deck: String,
+    front: String,
+    back: String,
+    lapses: usize,
+}
+
+/// A searchable, sortable table of every card.
+pub struct Browser {
+    rows: Vec<CardRow>,
+    // Names of every deck, for moving cards between them
+    decks: Vec<String>,
+    // Indices into `rows` of the cards matching the search, in sorted order
+    visible: Vec<usize>,
+    search: TextInput,
+    // Set while the search is not a valid query, which keeps the last results
+    search_error: Option<QueryError>,
+    sort: Column,
+    descending: bool,
+    table: TableState,
+    mode: Mode,
+    pub message: Option<String>,
+}
+
+impl Browser {
+    pub fn new(decks: &[Deck]) -> Self {
+        let mut browser = Self {
+            rows: Vec::new(),
+            decks: Vec::new(),
+            visible: Vec::new(),
+            search: TextInput::default(),
+            search_error: None,
+            sort: Column::Due,
+            descending: false,
+            table: TableState::default().with_selected(Some(0)),
+            mode: Mode::List,
+            message: None,
+        };
+        browser.refresh(decks);
+        browser
+    }
+
+    /// Reloads the rows after the decks changed, keeping the selected position.
+    pub fn refresh(&mut self, decks: &[Deck]) {
+        self.rows = Deck::locate_cards(decks)
+            .into_iter()
+            .map(|(location, card, deck)| CardRow {
+                location,
+                card: card.clone(),
+                deck: deck.name.clone(),
+                front: first_line(&card.front),
+                back: first_line(&card.back),
+                lapses: card.lapses(),
+            })
+            .collect();
+        self.decks = Deck::locate_decks(decks)
+            .into_iter()
+            .map(|(_, deck)| deck.name.clone())
+            .collect();
+        self.update_visible();
+    }
+
+    /// Whether keys are going to a text input, rather than acting as shortcuts.
+    pub fn is_typing(&self) -> bool {
+        matches!(self.mode, Mode::Search | Mode::Reschedule(_) | Mode::RenameDeck(_))
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) -> BrowserAction {
+        self.message = None;
+        let selected = self.selected().map(|row| row.location.clone());
+
+        match &mut self.mode {
+            Mode::Search => {
+                match key.code {
+                    KeyCode::Enter => self.mode = Mode::List,
+                    KeyCode::Esc => {
+                        self.search = TextInput::default();
+                        self.mode = Mode::List;
+                    }
+                    KeyCode::Up | KeyCode::Down => self.move_selection(if key.code == KeyCode::Up { -1 } else { 1 }),
+                    _ => {
+                        self.search.handle_key(key);
+                    }
+                }
+                self.update_visible();
+                BrowserAction::None
+            }
+            Mode::Reschedule(input) => match key.code {
+                KeyCode::Enter => {
+                    let days = input.value.trim().parse::<u64>();
+                    self.mode = Mode::List;
+                    match (days, selected) {
+                        (Ok(days), Some(location)) => BrowserAction::Reschedule(location, days),
+                        (Err(_), _) => {
+                            self.message = Some("Enter a number of days, 0 for now".to_string());
+                            BrowserAction::None
+                        }
+                        _ => BrowserAction::None,
+                    }
+                }
+                KeyCode::Esc => {
+                    self.mode = Mode::List;
+                    BrowserAction::None
+                }
+                _ => {
+                    input.handle_key(key);
+                    BrowserAction::None
+                }
+            },
+            Mode::ConfirmDelete => {
+                self.mode = Mode::List;
+                match (key.code, selected) {
+                    (KeyCode::Char('y'), Some(location)) => BrowserAction::Delete(location),
+                    _ => BrowserAction::None,
+                }
+            }
+            // Nothing to choose from without any decks
+            Mode::Move(_) if self.decks.is_empty() => {
+                self.mode = Mode::List;
+                BrowserAction::None
+            }
+            Mode::Move(deck) => match key.code {
+                KeyCode::Left | KeyCode::Up => {
+                    *deck = (*deck + self.decks.len() - 1) % self.decks.len();
+                    BrowserAction::None
+                }
+                KeyCode::Right | KeyCode::Down => {
+                    *deck = (*deck + 1) % self.decks.len();
+                    BrowserAction::None
+                }
+                KeyCode::Enter => {
+                    let deck = self.decks[*deck].clone();
+                    self.mode = Mode::List;
+                    selected.map_or(BrowserAction::None, |location| BrowserAction::Move(location, deck))
+                }
+                _ => {
+                    self.mode = Mode::List;
+                    BrowserAction::None
+                }
+            },
+            Mode::RenameDeck(input) => match key.code {
+                KeyCode::Enter => {
+                    let name = input.value.trim().trim_matches('/').to_string();
+                    self.mode = Mode::List;
+                    match self.selected() {
+                        Some(row) if name != row.deck && !name.is_empty() => BrowserAction::RenameDeck(row.deck.clone(), name),
+                        _ => BrowserAction::None,
+                    }
+                }
+                KeyCode::Esc => {
+                    self.mode = Mode::List;
+                    BrowserAction::None
+                }
+                _ => {
+                    input.handle_key(key);
+                    BrowserAction::None
+                }
+            },
+            Mode::Preview => {
+                match key.code {
+                    KeyCode::Char('e') => return selected.map_or(BrowserAction::None, BrowserAction::Edit),
+                    KeyCode::Esc | KeyCode::Enter | KeyCode::Char(' ') => self.mode = Mode::List,
+                    _ => {}
+                }
+                BrowserAction::None
+            }
+            Mode::List => self.handle_list_key(key, selected),
+        }
+    }
+
+    fn handle_list_key(&mut self, key: KeyEvent, selected: Option<CardLocation>) -> BrowserAction {
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('b') => return BrowserAction::Close,
+            KeyCode::Up | KeyCode::Char('k') => self.move_selection(-1),
+            KeyCode::Down | KeyCode::Char('j') => self.move_selection(1),
+            KeyCode::PageUp => self.move_selection(-10),
+            KeyCode::PageDown => self.move_selection(10),
+            KeyCode::Home => self.table.select(Some(0)),
+            KeyCode::End => self.table.select(Some(self.visible.len().saturating_sub(1))),
+            KeyCode::Char('/') => self.mode = Mode::Search,
+            KeyCode::Char(c @ '1'..='6') => {
+                let (column, _) = COLUMNS[c as usize - '1' as usize];
+                self.descending = column == self.sort && !self.descending;
+                self.sort = column;
+                self.update_visible();
+            }
+            _ => {}
+        }
+
+        let Some(location) = selected else {
+            return BrowserAction::None;
+        };
+        match key.code {
+            KeyCode::Enter => self.mode = Mode::Preview,
+            KeyCode::Char('e') => return BrowserAction::Edit(location),
+            KeyCode::Char('s') => return BrowserAction::ToggleSuspend(location),
+            KeyCode::Char('r') => self.mode = Mode::Reschedule(TextInput::default()),
+            KeyCode::Char('D') | KeyCode::Delete => self.mode = Mode::ConfirmDelete,
+            KeyCode::Char('m') if !self.decks.is_empty() => {
+                // Start from the card's own deck
+                let deck = self.selected()
+                    .and_then(|row| self.decks.iter().position(|name| *name == row.deck))
+                    .unwrap_or_default();
+                self.mode = Mode::Move(deck);
+            }
+            KeyCode::Char('R') => {
+                let deck = self.selected().map(|row| row.deck.as_str()).unwrap_or_default();
+                self.mode = Mode::RenameDeck(TextInput::new(deck));
+            }
+            _ => {}
+        }
+        BrowserAction::None
+    }
+
+    fn selected(&self) -> Option<&CardRow> {
+        let index = self.visible.get(self.table.selected()?)?;
+        self.rows.get(*index)
+    }
+
+    fn move_selection(&mut self, offset: i32) {
+        let last = self.visible.len().saturating_sub(1) as i32;
+        let selected = self.table.selected().unwrap_or(0) as i32;
+        self.table.select(Some((selected + offset).clamp(0, last) as usize));
+    }
+
+    fn update_visible(&mut self) {
+        let query = match Query::parse(&self.search.value) {
+            Ok(query) => query,
+            Err(e) => {
+                self.search_error = Some(e);
+                return;
+            }
+        };
+        self.search_error = None;
+        let now = current_unix_time();
+        let mut visible: Vec<usize> = (0..self.rows.len())
+            .filter(|&index| query.matches(&self.rows[index].card, &self.rows[index].deck, now))
+            .collect();
+
+        visible.sort_by(|&a, &b| {
+            let (a, b) = (&self.rows[a], &self.rows[b]);
+            let ordering = match self.sort {
+                Column::Front => a.front.to_lowercase().cmp(&b.front.to_lowercase()),
+                Column::Back => a.back.to_lowercase().cmp(&b.back.to_lowercase()),
+                Column::Deck => a.deck.cmp(&b.deck),
+                // Suspended cards are never due, so they go last
+                Column::Due => (a.card.suspended, a.card.next_review).cmp(&(b.card.suspended, b.card.next_review)),
+                Column::Interval => a.card.ease_factor.cmp(&b.card.ease_factor),
+                Column::Lapses => a.lapses.cmp(&b.lapses),
+            };
+            if self.descending { ordering.reverse() } else { ordering }
+        });
+
+        self.visible = visible;
+        let selected = self.table.selected().unwrap_or(0).min(self.visible.len().saturating_sub(1));
+        self.table.select(Some(selected));
+    }
+
+    pub fn draw(&mut self, f: &mut Frame, decks: &[Deck], config: &Config) {
+        if let Mode::Preview = self.mode {
+            if let Some(card) = self.selected().and_then(|row| Deck::card_at(decks, &row.location)) {
+                draw_full(f, card, false, config, &CardView::default(), None, None);
+            }
+            f.render_widget(frame_block(" Esc: back to the list │ e: edit "), f.area());
+            return;
+        }
+
+        let block = frame_block(" /: search │ 1-6: sort │ Enter: open │ e: edit │ s: suspend │ r: reschedule │ m: move │ R: rename deck │ D: delete │ Esc: close ");
+        let inner = block.inner(f.area());
+        f.render_widget(block, f.area());
+        let [search_area, table_area, status_area] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Min(0),
+            Constraint::Length(1),
+        ]).areas(inner);
+
+        let searching = matches!(self.mode, Mode::Search);
+        let search_style = if searching { Style::default().fg(Color::Yellow) } else { Style::default().fg(Color::DarkGray) };
+        let mut search_line = vec![Span::styled("/ ", search_style)];
+        match &self.search_error {
+            // Underline the part of the query the error is about
+            Some(error) => {
+                let chars: Vec<char> = self.search.value.chars().collect();
+                let part = |start: usize, end: usize| chars[start.min(chars.len())..end.min(chars.len())].iter().collect::<String>();
+                search_line.push(Span::raw(part(0, error.start)));
+                search_line.push(Span::styled(
+                    part(error.start, error.end),
+                    Style::default().fg(Color::Red).add_modifier(Modifier::UNDERLINED),
+                ));
+                search_line.push(Span::raw(part(error.end, chars.len())));
+            }
+            None => search_line.push(Span::raw(self.search.value.as_str())),
+        }
+        f.render_widget(Paragraph::new(Line::from(search_line)), search_area);
+        if searching {
+            f.set_cursor_position((search_area.x + 2 + self.search.cursor_offset(), search_area.y));
+        }
+
+        let now = current_unix_time();
+        let header = Row::new(COLUMNS.iter().map(|(column, title)| {
+            match (*column == self.sort, self.descending) {
+                (true, false) => format!("{} ▲", title),
+                (true, true) => format!("{} ▼", title),
+                (false, _) => title.to_string(),
+            }
+        })).style(Style::default().fg(Color::Yellow));
+        let rows = self.visible.iter().map(|&index| {
+            let row = &self.rows[index];
+            let due = match row.card.next_review {
+                _ if row.card.suspended => "suspended".to_string(),
+                Some(next_review) if next_review > now => format_date(next_review),
+                _ => "now".to_string(),
+            };
+            let style = if row.card.suspended { Style::default().fg(Color::DarkGray) } else { Style::default() };
+            // Flagged cards are marked in their flag's color
+            let front = match row.card.flag {
+                Some(flag) => Line::from(vec![
+                    Span::styled("⚑ ", Style::default().fg(flag_color(flag))),
+                    Span::raw(row.front.as_str()),
+                ]),
+                None => Line::raw(row.front.as_str()),
+            };
+            Row::new(vec![
+                front,
+                Line::raw(row.back.as_str()),
+                Line::raw(row.deck.as_str()),
+                Line::raw(due),
+                Line::raw(row.card.ease_factor.map(format_interval).unwrap_or_default()),
+                Line::raw(row.lapses.to_string()),
+            ]).style(style)
+        });
+        let table = Table::new(rows, [
+            Constraint::Fill(3),
+            Constraint::Fill(3),
+            Constraint::Fill(2),
+            Constraint::Length(10),
+            Constraint::Length(8),
+            Constraint::Length(8),
+        ])
+            .header(header)
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        f.render_stateful_widget(table, table_area, &mut self.table);
+
+        let status = match &self.mode {
+            Mode::Reschedule(input) => {
+                let prompt = "Due in how many days (0 = now): ";
+                f.set_cursor_position((status_area.x + prompt.len() as u16 + input.cursor_offset(), status_area.y));
+                Line::from(vec![Span::styled(prompt, Style::default().fg(Color::Yellow)), Span::raw(input.value.as_str())])
+            }
+            Mode::ConfirmDelete => Line::styled("Move this card to the trash? (y/n)", Style::default().fg(Color::Red)),
+            Mode::Move(deck) => Line::from(vec![
+                Span::styled("Move to deck: ", Style::default().fg(Color::Yellow)),
+                Span::raw(format!("◀ {} ▶", self.decks.get(*deck).map(String::as_str).unwrap_or_default())),
+                Span::styled("  ←/→: choose │ Enter: move │ Esc: cancel", Style::default().fg(Color::DarkGray)),
+            ]),
+            Mode::RenameDeck(input) => {
+                let prompt = "New deck name: ";
+                f.set_cursor_position((status_area.x + prompt.len() as u16 + input.cursor_offset(), status_area.y));
+                Line::from(vec![Span::styled(prompt, Style::default().fg(Color::Yellow)), Span::raw(input.value.as_str())])
+            }
+            _ => match (&self.search_error, &self.message) {
+                (Some(error), _) => Line::styled(error.message.as_str(), Style::default().fg(Color::Red)),
+                (None, Some(message)) => Line::styled(message.as_str(), Style::default().fg(Color::Red)),
+                (None, None) => Line::styled(
+                    format!("{} of {} cards", self.visible.len(), self.rows.len()),
+                    Style::default().fg(Color::DarkGray),
+                ),
+            },
+        };
+        f.render_widget(Paragraph::new(status), status_area);
+    }
+}
+
+fn frame_block(help: &str) -> Block<'_> {
+    Block::default()
+        .borders(Borders::ALL)
+        .title(Line::from(vec![
+            Span::raw(" 까먹다 "),
+            Span::raw("│ "),
+            Span::styled(" Browse ", Style::default().fg(Color::Yellow)),
+        ]))
+        .title(Title::from(help).position(Position::Bottom))
+}
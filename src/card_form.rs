@@ -0,0 +1,207 @@
+// card_form.rs
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::block::{Position, Title};
+use ratatui::widgets::{Block, BorderType, Borders, Clear, Paragraph};
+use ratatui::Frame;
+use crate::input::TextInput;
+use crate::model::{Card, Deck, ExampleSentence};
+use crate::ui::popup_area;
+
+const LABEL_WIDTH: u16 = 14;
+
+/// What the form asks the app to do once it is closed.
+pub enum FormAction {
+    None,
+    Cancel,
+    /// Add the card to the deck at this path in the deck tree
+    Save(Box<Card>, Vec<usize>),
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Field {
+    Front,
+    Back,
+    Notes,
+    Explanation,
+    Example,
+    Translation,
+    Reversible,
+    Deck,
+}
+
+const FIELDS: [(Field, &str); 8] = [
+    (Field::Front, "Front"),
+    (Field::Back, "Back"),
+    (Field::Notes, "Notes"),
+    (Field::Explanation, "Explanation"),
+    (Field::Example, "Example"),
+    (Field::Translation, "Translation"),
+    (Field::Reversible, "Reversible"),
+    (Field::Deck, "Deck"),
+];
+
+/// A popup for writing a new card and choosing its deck.
+pub struct CardForm {
+    front: TextInput,
+    back: TextInput,
+    notes: TextInput,
+    explanation: TextInput,
+    example: TextInput,
+    translation: TextInput,
+    reversible: bool,
+    // Every deck in the tree, with the indices leading to it
+    decks: Vec<(Vec<usize>, String)>,
+    deck: usize,
+    focus: usize,
+    pub error: Option<String>,
+}
+
+impl CardForm {
+    /// Starts an empty card in the deck named `current_deck`, or the first deck.
+    pub fn new(decks: &[Deck], current_deck: Option<&str>) -> Self {
+        let decks: Vec<(Vec<usize>, String)> = Deck::locate_decks(decks)
+            .into_iter()
+            .map(|(path, deck)| (path, deck.name.clone()))
+            .collect();
+        let deck = decks.iter()
+            .position(|(_, name)| Some(name.as_str()) == current_deck)
+            .unwrap_or_default();
+        Self {
+            front: TextInput::default(),
+            back: TextInput::default(),
+            notes: TextInput::default(),
+            explanation: TextInput::default(),
+            example: TextInput::default(),
+            translation: TextInput::default(),
+            reversible: Card::default_reversible(),
+            decks,
+            deck,
+            focus: 0,
+            error: None,
+        }
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) -> FormAction {
+        self.error = None;
+        let field = FIELDS[self.focus].0;
+        match key.code {
+            KeyCode::Esc => return FormAction::Cancel,
+            KeyCode::Char('s') if key.modifiers.contains(KeyModifiers::CONTROL) => return self.save(),
+            // Enter on the last field saves, like submitting a form
+            KeyCode::Enter if self.focus == FIELDS.len() - 1 => return self.save(),
+            KeyCode::Tab | KeyCode::Down | KeyCode::Enter => self.focus = (self.focus + 1) % FIELDS.len(),
+            KeyCode::BackTab | KeyCode::Up => self.focus = (self.focus + FIELDS.len() - 1) % FIELDS.len(),
+            KeyCode::Left | KeyCode::Right | KeyCode::Char(' ') if field == Field::Reversible => {
+                self.reversible = !self.reversible;
+            }
+            KeyCode::Left if field == Field::Deck => {
+                self.deck = (self.deck + self.decks.len().max(1) - 1) % self.decks.len().max(1);
+            }
+            KeyCode::Right if field == Field::Deck => self.deck = (self.deck + 1) % self.decks.len().max(1),
+            _ => {
+                if let Some(input) = self.input_mut(field) {
+                    input.handle_key(key);
+                }
+            }
+        }
+        FormAction::None
+    }
+
+    fn input(&self, field: Field) -> Option<&TextInput> {
+        match field {
+            Field::Front => Some(&self.front),
+            Field::Back => Some(&self.back),
+            Field::Notes => Some(&self.notes),
+            Field::Explanation => Some(&self.explanation),
+            Field::Example => Some(&self.example),
+            Field::Translation => Some(&self.translation),
+            Field::Reversible | Field::Deck => None,
+        }
+    }
+
+    fn input_mut(&mut self, field: Field) -> Option<&mut TextInput> {
+        match field {
+            Field::Front => Some(&mut self.front),
+            Field::Back => Some(&mut self.back),
+            Field::Notes => Some(&mut self.notes),
+            Field::Explanation => Some(&mut self.explanation),
+            Field::Example => Some(&mut self.example),
+            Field::Translation => Some(&mut self.translation),
+            Field::Reversible | Field::Deck => None,
+        }
+    }
+
+    fn save(&mut self) -> FormAction {
+        let text = |input: &TextInput| Some(input.value.trim().to_string()).filter(|value| !value.is_empty());
+        let (Some(front), Some(back)) = (text(&self.front), text(&self.back)) else {
+            self.error = Some("A card needs both a front and a back".to_string());
+            return FormAction::None;
+        };
+        let Some((deck, _)) = self.decks.get(self.deck) else {
+            self.error = Some("There is no deck to add the card to".to_string());
+            return FormAction::None;
+        };
+
+        let mut card = Card::new(front, back);
+        card.notes = text(&self.notes);
+        card.explanation = text(&self.explanation);
+        if let Some(sentence) = text(&self.example) {
+            card.examples = Some(vec![ExampleSentence {
+                sentence,
+                translation: text(&self.translation).unwrap_or_default(),
+            }]);
+        }
+        card.reversible = self.reversible;
+        FormAction::Save(Box::new(card), deck.clone())
+    }
+
+    pub fn draw(&self, f: &mut Frame) {
+        let area = popup_area(f.area(), 70, 60);
+        f.render_widget(Clear, area);
+        let block = Block::default()
+            .title(" New card ")
+            .title(Title::from(" Tab: next field │ Ctrl+s: save │ Esc: cancel ").position(Position::Bottom))
+            .borders(Borders::ALL)
+            .border_type(BorderType::Double);
+        let inner = block.inner(area);
+        f.render_widget(block, area);
+
+        let mut constraints = vec![Constraint::Length(1)];
+        constraints.extend(FIELDS.iter().map(|_| Constraint::Length(1)));
+        constraints.extend([Constraint::Length(1), Constraint::Length(1)]);
+        let rows = Layout::vertical(constraints).split(inner);
+
+        for (index, (field, label)) in FIELDS.iter().enumerate() {
+            let area = rows[index + 1];
+            let focused = index == self.focus;
+            let label_style = if focused { Style::default().fg(Color::Yellow) } else { Style::default().fg(Color::DarkGray) };
+            let [label_area, value_area] = Layout::horizontal([Constraint::Length(LABEL_WIDTH), Constraint::Min(0)])
+                .areas(area);
+            f.render_widget(Paragraph::new(Span::styled(format!(" {}", label), label_style)), label_area);
+
+            let value = match field {
+                Field::Reversible => Line::raw(if self.reversible { "[x]" } else { "[ ]" }),
+                Field::Deck => Line::raw(match self.decks.get(self.deck) {
+                    Some((_, name)) => format!("◀ {} ▶", name),
+                    None => "No decks".to_string(),
+                }),
+                _ => Line::raw(self.input(*field).map(|input| input.value.as_str()).unwrap_or_default()),
+            };
+            // Long values scroll sideways to keep the cursor in view
+            let scroll = self.input(*field).map(|input| input.scroll(value_area.width)).unwrap_or_default();
+            f.render_widget(Paragraph::new(value).scroll((0, scroll)), value_area);
+
+            if let (true, Some(input)) = (focused, self.input(*field)) {
+                f.set_cursor_position((value_area.x + input.cursor_offset() - scroll, value_area.y));
+            }
+        }
+
+        if let Some(error) = &self.error {
+            let area = rows[FIELDS.len() + 2];
+            f.render_widget(Paragraph::new(Line::styled(format!(" {}", error), Style::default().fg(Color::Red))), area);
+        }
+    }
+}
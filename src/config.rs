@@ -0,0 +1,77 @@
+// config.rs
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use color_eyre::Result;
+use ratatui_image::picker::ProtocolType;
+use crate::model::Flag;
+use crate::ruby::RubyMode;
+use serde::Deserialize;
+
+/// Settings read from `~/.config/kkameokda/config.yaml`. Every setting is
+/// optional, so a missing file or key falls back to the defaults.
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// Render card text as Markdown instead of showing it verbatim.
+    pub markdown: bool,
+    /// syntect theme used to highlight fenced code blocks.
+    pub code_theme: String,
+    /// Terminal graphics protocol for card images: `kitty`, `sixel`,
+    /// `iterm2` or `halfblocks`. Detected from the terminal when unset.
+    pub image_protocol: Option<ProtocolType>,
+    /// Command that plays a card's `audio` file, given in place of `{file}`.
+    pub audio_command: String,
+    /// Text-to-speech command used for cards without audio. The text to
+    /// speak is written to its standard input.
+    pub tts_command: Option<String>,
+    /// Play the front of each card as soon as it is shown.
+    pub autoplay: bool,
+    /// Show the romanization of Hangul from the start of a session.
+    pub romanization: bool,
+    /// Convert numbered pinyin such as `ma3` into tone marks.
+    pub pinyin_tone_marks: bool,
+    /// Where `{text|reading}` annotations show their reading: `above`,
+    /// `beside` or `hidden`.
+    pub ruby: RubyMode,
+    /// Start notes added during a review with the date they were written.
+    pub timestamp_notes: bool,
+    /// What each flag color means, such as `red: wrong translation`.
+    pub flag_labels: BTreeMap<Flag, String>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            markdown: true,
+            code_theme: "base16-ocean.dark".to_string(),
+            image_protocol: None,
+            audio_command: "mpv --no-video --really-quiet {file}".to_string(),
+            tts_command: None,
+            autoplay: false,
+            romanization: false,
+            pinyin_tone_marks: true,
+            ruby: RubyMode::Above,
+            timestamp_notes: false,
+            flag_labels: BTreeMap::new(),
+        }
+    }
+}
+
+impl Config {
+    /// The label of `flag`, or its color when none is configured.
+    pub fn flag_label(&self, flag: Flag) -> &str {
+        self.flag_labels.get(&flag).map_or(flag.name(), String::as_str)
+    }
+}
+
+pub fn config_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("kkameokda").join("config.yaml"))
+}
+
+pub fn load_config() -> Result<Config> {
+    match config_path() {
+        Some(path) if path.exists() => Ok(serde_yaml::from_str(&fs::read_to_string(path)?)?),
+        _ => Ok(Config::default()),
+    }
+}
@@ -0,0 +1,395 @@
+// dupes.rs
+use std::collections::BTreeMap;
+use std::io::{self, BufRead, Write};
+use color_eyre::Result;
+use crate::model::{Card, Deck};
+use crate::trash::trash_card;
+use crate::utilities::{first_line, relative_path};
+
+/// How the cards of a group duplicate each other, from closest to loosest.
+#[derive(Clone, Copy)]
+pub enum DuplicateKind {
+    Exact,
+    /// The same apart from case, spacing and punctuation
+    Normalized,
+    SameFront,
+    /// One card's front is the other's back
+    Reversed,
+}
+
+impl DuplicateKind {
+    fn describe(self) -> &'static str {
+        match self {
+            DuplicateKind::Exact => "Exact duplicates",
+            DuplicateKind::Normalized => "Duplicates apart from case, spacing and punctuation",
+            DuplicateKind::SameFront => "Same front, different back",
+            DuplicateKind::Reversed => "Reversed duplicates",
+        }
+    }
+}
+
+/// Cards that duplicate each other, by their index in the list searched.
+pub struct DuplicateGroup {
+    pub kind: DuplicateKind,
+    pub cards: Vec<usize>,
+}
+
+enum Choice {
+    Keep(usize),
+    Skip,
+    Quit,
+}
+
+/// Groups the duplicates among `cards`. A card can be in more than one group,
+/// for example when it has both an exact and a reversed duplicate.
+pub fn find_duplicates(cards: &[Card]) -> Vec<DuplicateGroup> {
+    let keys: Vec<(String, String)> = cards.iter()
+        .map(|card| (normalize(&card.front), normalize(&card.back)))
+        .collect();
+    let mut by_text: BTreeMap<&(String, String), Vec<usize>> = BTreeMap::new();
+    let mut by_front: BTreeMap<&str, Vec<usize>> = BTreeMap::new();
+    for (index, key) in keys.iter().enumerate() {
+        // Nothing is left of fronts made only of punctuation to compare
+        if key.0.is_empty() {
+            continue;
+        }
+        by_text.entry(key).or_default().push(index);
+        by_front.entry(&key.0).or_default().push(index);
+    }
+
+    let mut groups = Vec::new();
+    for indices in by_text.values().filter(|indices| indices.len() > 1) {
+        let first = &cards[indices[0]];
+        let exact = indices.iter().all(|&index| cards[index].front == first.front && cards[index].back == first.back);
+        groups.push(DuplicateGroup {
+            kind: if exact { DuplicateKind::Exact } else { DuplicateKind::Normalized },
+            cards: indices.clone(),
+        });
+    }
+    for indices in by_front.values() {
+        if indices.iter().any(|&index| keys[index].1 != keys[indices[0]].1) {
+            groups.push(DuplicateGroup { kind: DuplicateKind::SameFront, cards: indices.clone() });
+        }
+    }
+    for (key, indices) in &by_text {
+        let reversed = (key.1.clone(), key.0.clone());
+        // Each pair is found from both sides, so only the first one counts
+        if **key >= reversed {
+            continue;
+        }
+        if let Some(others) = by_text.get(&reversed) {
+            groups.push(DuplicateGroup {
+                kind: DuplicateKind::Reversed,
+                cards: indices.iter().chain(others).copied().collect(),
+            });
+        }
+    }
+    groups
+}
+
+// Case, punctuation, Markdown emphasis and spacing don't make two cards different
+fn normalize(text: &str) -> String {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+/// Prints every group of duplicate cards in `decks` with their files. With
+/// `merge`, asks for each group which card to keep, merges the others into it
+/// and moves them to the trash.
+pub fn report_duplicates(decks: &[Deck], merge: bool) -> Result<()> {
+    let mut cards: Vec<Card> = Deck::locate_cards(decks)
+        .into_iter()
+        .map(|(_, card, _)| card.clone())
+        .collect();
+    let groups = find_duplicates(&cards);
+    if groups.is_empty() {
+        eprintln!("No duplicate cards found");
+        return Ok(());
+    }
+    if !merge {
+        for group in &groups {
+            print_group(group, &cards, &group.cards, false);
+        }
+        eprintln!("{} groups of duplicates, merge them with `kkameokda dupes --merge`", groups.len());
+        return Ok(());
+    }
+
+    let mut removed = vec![false; cards.len()];
+    for group in &groups {
+        // Earlier merges may have removed some of the cards already
+        let members: Vec<usize> = group.cards.iter().copied().filter(|&index| !removed[index]).collect();
+        if members.len() < 2 {
+            continue;
+        }
+        print_group(group, &cards, &members, true);
+
+        let inline: Vec<usize> = members.iter().copied().filter(|&index| cards[index].inline_id.is_some()).collect();
+        if inline.len() > 1 {
+            println!("Cards inside notes are merged by editing the notes, skipping\n");
+            continue;
+        }
+        let kept = match choose(&members, inline.first().copied())? {
+            Choice::Keep(kept) => kept,
+            Choice::Skip => {
+                println!();
+                continue;
+            }
+            Choice::Quit => break,
+        };
+
+        let others: Vec<usize> = members.iter().copied().filter(|&index| index != kept).collect();
+        let mut merged = cards[kept].clone();
+        for &other in &others {
+            merge_card(&mut merged, &cards[other]);
+        }
+        // Only the review state of a card inside a note is saved outside it
+        let unsaved = unsaved_fields(&cards[kept], &merged);
+        if !unsaved.is_empty() {
+            println!(
+                "The card inside {} would not keep the others' {}; merge them by editing the note, skipping\n",
+                location(&cards[kept]), unsaved.join(", "),
+            );
+            continue;
+        }
+        merged.save()?;
+        cards[kept] = merged;
+
+        for other in others {
+            let entry = trash_card(&cards[other])?;
+            println!("Moved {} to the trash ({})", entry.path, entry.id);
+            removed[other] = true;
+            shift_file_indices(&mut cards, other);
+        }
+        println!();
+    }
+    Ok(())
+}
+
+fn print_group(group: &DuplicateGroup, cards: &[Card], members: &[usize], numbered: bool) {
+    println!("{}:", group.kind.describe());
+    let locations: Vec<String> = members.iter().map(|&index| location(&cards[index])).collect();
+    let width = locations.iter().map(|location| location.chars().count()).max().unwrap_or_default();
+    for (number, (&index, location)) in members.iter().zip(&locations).enumerate() {
+        let card = &cards[index];
+        let reviews = card.history.as_deref().unwrap_or_default().len();
+        let prefix = if numbered { format!("{}. ", number + 1) } else { String::new() };
+        println!(
+            "  {}{:<width$}  {} → {}  ({} reviews)",
+            prefix, location, first_line(&card.front), first_line(&card.back), reviews,
+        );
+    }
+    if !numbered {
+        println!();
+    }
+}
+
+// The card's file, and its position when the file holds a list of cards
+fn location(card: &Card) -> String {
+    let path = card.file_path.as_deref().map(relative_path).unwrap_or_default();
+    match (card.file_index, &card.inline_id) {
+        (Some(index), _) => format!("{} #{}", path, index + 1),
+        (None, Some(_)) => format!("{} (note)", path),
+        (None, None) => path,
+    }
+}
+
+// Asks which of `members` to keep. Cards inside notes cannot be moved to the
+// trash, so when there is one it is the card that has to be kept.
+fn choose(members: &[usize], required: Option<usize>) -> Result<Choice> {
+    let default = required.and_then(|index| members.iter().position(|&member| member == index)).unwrap_or_default();
+    loop {
+        print!("Keep which card, merging the others into it? [1-{}, s: skip, q: quit] ({}) ", members.len(), default + 1);
+        io::stdout().flush()?;
+        let mut answer = String::new();
+        if io::stdin().lock().read_line(&mut answer)? == 0 {
+            return Ok(Choice::Quit);
+        }
+        let chosen = match answer.trim() {
+            "" => default,
+            "s" => return Ok(Choice::Skip),
+            "q" => return Ok(Choice::Quit),
+            number => match number.parse::<usize>() {
+                Ok(number) if (1..=members.len()).contains(&number) => number - 1,
+                _ => continue,
+            },
+        };
+        if required.is_some_and(|index| index != members[chosen]) {
+            println!("The card inside a note has to be the one kept, since it can only be removed by editing the note");
+            continue;
+        }
+        return Ok(Choice::Keep(members[chosen]));
+    }
+}
+
+/// Folds `other` into `kept`: its reviews are added to the history, a
+/// different back is added as another answer, and fields `kept` leaves empty
+/// are filled in from it. The schedule of the card reviewed last wins.
+pub fn merge_card(kept: &mut Card, other: &Card) {
+    let last_review = |card: &Card| card.history.as_deref().unwrap_or_default().iter().map(|review| review.date).max();
+    if last_review(other) > last_review(kept) {
+        kept.next_review = other.next_review;
+        kept.ease_factor = other.ease_factor;
+    }
+
+    // Reviewing a reversed duplicate is reviewing this card the other way around
+    let reversed = normalize(&other.front) != normalize(&kept.front) && normalize(&other.front) == normalize(&kept.back);
+    let mut history = kept.history.take().unwrap_or_default();
+    for review in other.history.iter().flatten() {
+        let mut review = review.clone();
+        if reversed {
+            review.reversed = Some(!review.reversed.unwrap_or_default());
+        }
+        if !history.iter().any(|existing| existing.date == review.date && existing.remembered == review.remembered) {
+            history.push(review);
+        }
+    }
+    history.sort_by_key(|review| review.date);
+    kept.history = Some(history);
+    kept.reversible |= reversed;
+
+    // The same front can have another meaning, as 배 is a pear, a ship and a
+    // belly, so a different back is kept as another answer
+    let answers: Vec<String> = kept.back.split(';').map(normalize).collect();
+    if !reversed && !answers.contains(&normalize(&other.back)) {
+        let separator = if kept.back.contains('\n') || other.back.contains('\n') { "\n\n" } else { "; " };
+        kept.back = format!("{}{}{}", kept.back, separator, other.back);
+    }
+
+    kept.suspended &= other.suspended;
+    kept.notes = kept.notes.take().or_else(|| other.notes.clone());
+    kept.explanation = kept.explanation.take().or_else(|| other.explanation.clone());
+    kept.examples = kept.examples.take().or_else(|| other.examples.clone());
+    kept.flag = kept.flag.or(other.flag);
+    kept.added = match (kept.added, other.added) {
+        (Some(kept_added), Some(other_added)) => Some(kept_added.min(other_added)),
+        (kept_added, other_added) => kept_added.or(other_added),
+    };
+    for tag in &other.tags {
+        if !kept.tags.contains(tag) {
+            kept.tags.push(tag.clone());
+        }
+    }
+}
+
+/// The fields merging changed in `merged` that would not be saved, because
+/// `kept` is a card inside a note and only its review state is stored. Such
+/// cards have no date added, so that is not counted.
+pub fn unsaved_fields(kept: &Card, merged: &Card) -> Vec<&'static str> {
+    if kept.inline_id.is_none() {
+        return Vec::new();
+    }
+    let examples = |card: &Card| -> Vec<(String, String)> {
+        card.examples.iter().flatten()
+            .map(|example| (example.sentence.clone(), example.translation.clone()))
+            .collect()
+    };
+    [
+        ("back", kept.back != merged.back),
+        ("notes", kept.notes != merged.notes),
+        ("explanation", kept.explanation != merged.explanation),
+        ("examples", examples(kept) != examples(merged)),
+        ("reversibility", kept.reversible != merged.reversible),
+        ("tags", kept.tags != merged.tags),
+    ]
+    .into_iter()
+    .filter_map(|(field, changed)| changed.then_some(field))
+    .collect()
+}
+
+// Cards after `removed` in the same file moved up one place when it was taken out
+fn shift_file_indices(cards: &mut [Card], removed: usize) {
+    let (Some(path), Some(removed_index)) = (cards[removed].file_path.clone(), cards[removed].file_index) else {
+        return;
+    };
+    for card in cards.iter_mut() {
+        if card.file_path.as_ref() == Some(&path) && card.file_index.is_some_and(|index| index > removed_index) {
+            card.file_index = card.file_index.map(|index| index - 1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::ReviewHistory;
+
+    fn card(front: &str, back: &str) -> Card {
+        Card::new(front.to_string(), back.to_string())
+    }
+
+    fn review(date: u64, reversed: Option<bool>) -> ReviewHistory {
+        ReviewHistory { date, remembered: true, reversed, interval: None, partial: None }
+    }
+
+    #[test]
+    fn finds_each_kind_of_duplicate() {
+        let cards = [
+            card("안녕", "hi/bye"),
+            card("안녕", "hi/bye"),
+            card("**하나**", "One."),
+            card("하나", "one"),
+            card("hi/bye", "안녕"),
+            card("배", "pear"),
+            card("배", "ship"),
+        ];
+        let groups: Vec<(&str, Vec<usize>)> = find_duplicates(&cards)
+            .into_iter()
+            .map(|group| (group.kind.describe(), group.cards))
+            .collect();
+        assert!(groups.contains(&(DuplicateKind::Exact.describe(), vec![0, 1])));
+        assert!(groups.contains(&(DuplicateKind::Normalized.describe(), vec![2, 3])));
+        assert!(groups.contains(&(DuplicateKind::SameFront.describe(), vec![5, 6])));
+        assert!(groups.contains(&(DuplicateKind::Reversed.describe(), vec![4, 0, 1])));
+        assert_eq!(groups.len(), 4);
+    }
+
+    #[test]
+    fn merging_keeps_every_back_of_the_same_front() {
+        let mut kept = card("배", "pear");
+        merge_card(&mut kept, &card("배", "ship"));
+        merge_card(&mut kept, &card("배", "belly"));
+        merge_card(&mut kept, &card("배", "Ship"));
+        assert_eq!(kept.back, "pear; ship; belly");
+    }
+
+    #[test]
+    fn cards_inside_notes_only_take_review_state_from_a_merge() {
+        let mut kept = card("배", "pear");
+        kept.inline_id = Some("0123456789abcdef".to_string());
+        kept.reversible = false;
+
+        let mut same = card("배", "Pear");
+        same.reversible = false;
+        same.history = Some(vec![review(1, None)]);
+        let mut merged = kept.clone();
+        merge_card(&mut merged, &same);
+        assert!(unsaved_fields(&kept, &merged).is_empty());
+
+        let mut other = card("배", "ship");
+        other.notes = Some("Also a belly".to_string());
+        other.tags = vec!["food".to_string()];
+        let mut merged = kept.clone();
+        merge_card(&mut merged, &other);
+        assert_eq!(unsaved_fields(&kept, &merged), vec!["back", "notes", "tags"]);
+
+        kept.inline_id = None;
+        assert!(unsaved_fields(&kept, &merged).is_empty());
+    }
+
+    #[test]
+    fn merging_a_reversed_duplicate_flips_its_reviews() {
+        let mut kept = card("안녕", "hi");
+        kept.history = Some(vec![review(1, None)]);
+        let mut other = card("hi", "안녕");
+        other.history = Some(vec![review(2, None), review(3, Some(true))]);
+        merge_card(&mut kept, &other);
+
+        let reversed: Vec<Option<bool>> = kept.history.iter().flatten().map(|review| review.reversed).collect();
+        assert_eq!(reversed, vec![None, Some(true), Some(false)]);
+        assert_eq!(kept.back, "hi");
+        assert!(kept.reversible);
+    }
+}
@@ -0,0 +1,30 @@
+// editor.rs
+use std::env;
+use std::path::Path;
+use std::process::Command;
+use color_eyre::{eyre::eyre, Result};
+
+/// The user's editor: `$VISUAL`, then `$EDITOR`, then `vi`.
+pub fn editor_command() -> String {
+    ["VISUAL", "EDITOR"].iter()
+        .filter_map(|name| env::var(name).ok())
+        .find(|command| !command.trim().is_empty())
+        .unwrap_or_else(|| "vi".to_string())
+}
+
+/// Runs the editor on `path` and waits for it to exit. The editor command may
+/// include arguments, as in `code --wait`.
+pub fn edit_file(path: &Path) -> Result<()> {
+    let command = editor_command();
+    let mut parts = command.split_whitespace();
+    let program = parts.next().ok_or_else(|| eyre!("No editor configured"))?;
+    let status = Command::new(program)
+        .args(parts)
+        .arg(path)
+        .status()
+        .map_err(|e| eyre!("Could not run {}: {}", program, e))?;
+    if !status.success() {
+        return Err(eyre!("{} exited with {}", program, status));
+    }
+    Ok(())
+}
@@ -0,0 +1,255 @@
+// export.rs
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::Path;
+use color_eyre::{eyre::eyre, Result};
+use serde::Serialize;
+use crate::args::LogFormat;
+use crate::model::{Card, Deck};
+use crate::utilities::{format_date, relative_path, stable_hash};
+
+const DAY: u64 = 24 * 60 * 60;
+
+// One review, with column names following Anki's revlog table where one exists
+#[derive(Serialize)]
+struct RevlogEntry {
+    id: u64,
+    cid: u64,
+    deck: String,
+    file: String,
+    direction: &'static str,
+    ease: u8,
+    ivl: Option<i64>,
+    #[serde(rename = "lastIvl")]
+    last_ivl: Option<i64>,
+    #[serde(rename = "type")]
+    review_type: u8,
+    timestamp: u64,
+}
+
+pub fn export_log(decks: &[Deck], format: LogFormat, output: Option<&str>) -> Result<()> {
+    let entries = collect_revlog(decks);
+
+    let writer = output_writer(output.map(Path::new))?;
+
+    match format {
+        LogFormat::Csv => {
+            let mut csv_writer = csv::Writer::from_writer(writer);
+            for entry in &entries {
+                csv_writer.serialize(entry)?;
+            }
+            csv_writer.flush()?;
+        }
+        LogFormat::Json => {
+            let mut writer = writer;
+            serde_json::to_writer_pretty(&mut writer, &entries)?;
+            writeln!(writer)?;
+        }
+    }
+
+    Ok(())
+}
+
+pub fn export_csv(decks: &[&Deck], output: Option<&Path>, delimiter: Option<char>, schedule: bool) -> Result<()> {
+    let delimiter = delimiter.unwrap_or_else(|| {
+        match output.and_then(|path| path.extension()).and_then(|ext| ext.to_str()) {
+            Some("tsv" | "tab") => '\t',
+            _ => ',',
+        }
+    });
+    let mut writer = csv::WriterBuilder::new()
+        .delimiter(u8::try_from(delimiter).map_err(|_| eyre!("Delimiter must be a single-byte character"))?)
+        .from_writer(output_writer(output)?);
+
+    let mut header = vec!["deck", "file", "front", "back", "notes", "explanation", "examples", "reversible"];
+    if schedule {
+        header.extend(["next_review", "interval_days", "reviews", "lapses"]);
+    }
+    writer.write_record(&header)?;
+
+    for deck in decks.iter().flat_map(|deck| deck.flatten()) {
+        for card in &deck.cards {
+            let mut record = vec![
+                deck.name.clone(),
+                relative_card_path(card),
+                card.front.clone(),
+                card.back.clone(),
+                card.notes.clone().unwrap_or_default(),
+                card.explanation.clone().unwrap_or_default(),
+                examples_text(card).join("\n"),
+                card.reversible.to_string(),
+            ];
+            if schedule {
+                let stats = ScheduleStats::of(card);
+                record.extend([stats.next_review, stats.interval_days, stats.reviews.to_string(), stats.lapses.to_string()]);
+            }
+            writer.write_record(&record)?;
+        }
+    }
+
+    writer.flush()?;
+    Ok(())
+}
+
+pub fn export_markdown(decks: &[&Deck], output: Option<&Path>, schedule: bool) -> Result<()> {
+    let mut writer = output_writer(output)?;
+
+    for deck in decks.iter().flat_map(|deck| deck.flatten()) {
+        if deck.cards.is_empty() {
+            continue;
+        }
+        writeln!(writer, "# {}\n", deck.name)?;
+
+        for card in &deck.cards {
+            writeln!(writer, "## {}\n", card.front)?;
+            writeln!(writer, "{}\n", card.back)?;
+
+            if let Some(explanation) = &card.explanation {
+                writeln!(writer, "**Explanation:** {}\n", explanation)?;
+            }
+            let examples = examples_text(card);
+            if !examples.is_empty() {
+                writeln!(writer, "**Examples:**\n")?;
+                for example in examples {
+                    writeln!(writer, "- {}", example)?;
+                }
+                writeln!(writer)?;
+            }
+            if let Some(notes) = &card.notes {
+                writeln!(writer, "**Notes:** {}\n", notes)?;
+            }
+            if schedule {
+                let stats = ScheduleStats::of(card);
+                writeln!(
+                    writer,
+                    "*Next review: {} · interval: {} days · reviews: {} · lapses: {}*\n",
+                    stats.next_review, stats.interval_days, stats.reviews, stats.lapses
+                )?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn output_writer(output: Option<&Path>) -> Result<Box<dyn Write>> {
+    Ok(match output {
+        Some(path) => Box::new(File::create(path)?),
+        None => Box::new(io::stdout().lock()),
+    })
+}
+
+// Examples as `sentence - translation`, the same shape the importers read back
+fn examples_text(card: &Card) -> Vec<String> {
+    card.examples.as_deref()
+        .unwrap_or_default()
+        .iter()
+        .map(|example| format!("{} - {}", example.sentence, example.translation))
+        .collect()
+}
+
+struct ScheduleStats {
+    next_review: String,
+    interval_days: String,
+    reviews: usize,
+    lapses: usize,
+}
+
+impl ScheduleStats {
+    fn of(card: &Card) -> Self {
+        Self {
+            next_review: card.next_review.map(format_date).unwrap_or_default(),
+            interval_days: card.ease_factor.map(|interval| format!("{:.1}", interval as f64 / DAY as f64)).unwrap_or_default(),
+            reviews: card.history.as_ref().map_or(0, Vec::len),
+            lapses: card.lapses(),
+        }
+    }
+}
+
+fn collect_revlog(decks: &[Deck]) -> Vec<RevlogEntry> {
+    let mut entries = Vec::new();
+
+    for deck in decks.iter().flat_map(|deck| deck.flatten()) {
+        for card in &deck.cards {
+            let file = relative_card_path(card);
+            let cid = card_id(card);
+            let history = card.history.as_deref().unwrap_or_default();
+            let mut last_ivl = Some(0);
+
+            for (index, review) in history.iter().enumerate() {
+                let ivl = review.interval.map(anki_interval);
+                entries.push(RevlogEntry {
+                    id: review.date * 1000,
+                    cid,
+                    deck: deck.name.clone(),
+                    file: file.clone(),
+                    direction: match review.reversed {
+                        Some(true) => "reverse",
+                        Some(false) => "forward",
+                        None => "",
+                    },
+                    ease: match (review.remembered, review.partial) {
+                        (false, _) => 1,
+                        (true, Some(true)) => 2,
+                        (true, _) => 3,
+                    },
+                    ivl,
+                    last_ivl,
+                    review_type: if index == 0 { 0 } else { 1 },
+                    timestamp: review.date,
+                });
+                last_ivl = ivl;
+            }
+        }
+    }
+
+    entries.sort_by_key(|entry| entry.id);
+    entries
+}
+
+/// Picks the deck named `name` (for example `Korean/grammar`) out of the
+/// loaded tree, or every deck when no name is given.
+pub fn select_decks<'a>(decks: &'a [Deck], name: Option<&str>) -> Result<Vec<&'a Deck>> {
+    let Some(name) = name else {
+        return Ok(decks.iter().collect());
+    };
+    let name = name.trim_matches('/');
+    decks.iter()
+        .flat_map(|deck| deck.flatten())
+        .find(|deck| deck.name == name)
+        .map(|deck| vec![deck])
+        .ok_or_else(|| eyre!("No deck named {:?}", name))
+}
+
+/// Path of the card file relative to the flashcards directory.
+pub fn relative_card_path(card: &Card) -> String {
+    card.file_path.as_deref()
+        .map(relative_path)
+        .unwrap_or_default()
+}
+
+/// Identifies a card across runs: its file, plus its place within the file
+/// for files holding several cards.
+pub fn card_key(card: &Card) -> String {
+    let file = relative_card_path(card);
+    match (&card.inline_id, card.file_index) {
+        (Some(id), _) => format!("{}#{}", file, id),
+        (None, Some(index)) => format!("{}#{}", file, index),
+        (None, None) => file,
+    }
+}
+
+/// Numeric card ID derived from the card's key, kept below 2^53 so it
+/// survives a round trip through JSON tooling.
+pub fn card_id(card: &Card) -> u64 {
+    stable_hash(&card_key(card)) & ((1 << 53) - 1)
+}
+
+// Anki stores intervals as positive days, or negative seconds when under a day
+fn anki_interval(seconds: u64) -> i64 {
+    if seconds >= DAY {
+        ((seconds + DAY / 2) / DAY) as i64
+    } else {
+        -(seconds as i64)
+    }
+}
@@ -0,0 +1,374 @@
+// format.rs
+use std::path::Path;
+use color_eyre::{eyre::eyre, Result};
+use serde::{Deserialize, Serialize};
+use serde_yaml::{Mapping, Value};
+use crate::model::Card;
+
+// Card fields that live in the body of a Markdown card rather than its frontmatter
+const MARKDOWN_SECTIONS: [&str; 5] = ["front", "back", "examples", "explanation", "notes"];
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum CardFormat {
+    Yaml,
+    Json,
+    Toml,
+    Markdown,
+}
+
+// TOML documents must be tables, so a list of cards is written as `[[cards]]`
+#[derive(Deserialize, Serialize)]
+struct TomlCards {
+    cards: Vec<Card>,
+}
+
+impl CardFormat {
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()? {
+            "yaml" | "yml" => Some(CardFormat::Yaml),
+            "json" => Some(CardFormat::Json),
+            "toml" => Some(CardFormat::Toml),
+            "md" => Some(CardFormat::Markdown),
+            _ => None,
+        }
+    }
+
+    /// Parses a card file. YAML, JSON and TOML files may hold a list of cards,
+    /// in which case the cards are returned with their position in the list.
+    pub fn parse(self, contents: &str) -> Result<Vec<(Card, Option<usize>)>> {
+        match self {
+            CardFormat::Yaml => {
+                let value: Value = serde_yaml::from_str(contents)?;
+                if value.is_sequence() {
+                    Ok(indexed(serde_yaml::from_value(value)?))
+                } else {
+                    Ok(vec![(serde_yaml::from_value(value)?, None)])
+                }
+            }
+            CardFormat::Json => {
+                let value: serde_json::Value = serde_json::from_str(contents)?;
+                if value.is_array() {
+                    Ok(indexed(serde_json::from_value(value)?))
+                } else {
+                    Ok(vec![(serde_json::from_value(value)?, None)])
+                }
+            }
+            CardFormat::Toml => {
+                let table: toml::Table = toml::from_str(contents)?;
+                if table.contains_key("cards") {
+                    Ok(indexed(table.try_into::<TomlCards>()?.cards))
+                } else {
+                    Ok(vec![(table.try_into()?, None)])
+                }
+            }
+            CardFormat::Markdown => Ok(vec![(parse_markdown(contents)?, None)]),
+        }
+    }
+
+    /// Renders `card` for writing back over `existing`, the current contents of its file.
+    pub fn write(self, card: &Card, file_index: Option<usize>, existing: &str) -> Result<String> {
+        if self == CardFormat::Markdown {
+            return write_markdown(card, existing);
+        }
+
+        let Some(index) = file_index else {
+            return match self {
+                CardFormat::Json => Ok(serde_json::to_string_pretty(card)? + "\n"),
+                CardFormat::Toml => Ok(toml::to_string(card)?),
+                _ => Ok(serde_yaml::to_string(card)?),
+            };
+        };
+
+        let mut cards: Vec<Card> = match self {
+            CardFormat::Json => serde_json::from_str(existing)?,
+            CardFormat::Toml => toml::from_str::<TomlCards>(existing)?.cards,
+            _ => serde_yaml::from_str(existing)?,
+        };
+        let entry = cards.get_mut(index)
+            .ok_or_else(|| eyre!("Card {} no longer exists in its file", index))?;
+        *entry = card.clone();
+
+        match self {
+            CardFormat::Json => Ok(serde_json::to_string_pretty(&cards)? + "\n"),
+            CardFormat::Toml => Ok(toml::to_string(&TomlCards { cards })?),
+            _ => Ok(serde_yaml::to_string(&cards)?),
+        }
+    }
+
+    /// Removes card `index` from a file holding a list of cards. Returns None
+    /// when no cards are left, so the file can be deleted.
+    pub fn remove(self, index: usize, existing: &str) -> Result<Option<String>> {
+        let mut cards: Vec<Card> = match self {
+            CardFormat::Json => serde_json::from_str(existing)?,
+            CardFormat::Toml => toml::from_str::<TomlCards>(existing)?.cards,
+            CardFormat::Yaml => serde_yaml::from_str(existing)?,
+            CardFormat::Markdown => return Ok(None),
+        };
+        if index >= cards.len() {
+            return Err(eyre!("Card {} no longer exists in its file", index));
+        }
+        cards.remove(index);
+        if cards.is_empty() {
+            return Ok(None);
+        }
+
+        match self {
+            CardFormat::Json => Ok(Some(serde_json::to_string_pretty(&cards)? + "\n")),
+            CardFormat::Toml => Ok(Some(toml::to_string(&TomlCards { cards })?)),
+            _ => Ok(Some(serde_yaml::to_string(&cards)?)),
+        }
+    }
+}
+
+fn indexed(cards: Vec<Card>) -> Vec<(Card, Option<usize>)> {
+    cards.into_iter()
+        .enumerate()
+        .map(|(index, card)| (card, Some(index)))
+        .collect()
+}
+
+/// Markdown files with a `## Front` section are cards; any other Markdown
+/// file is a note that may contain inline cards.
+pub fn is_markdown_card(contents: &str) -> bool {
+    markdown_sections(contents).iter().any(|(name, _)| name == "front")
+}
+
+// A Markdown card is YAML frontmatter for metadata and review state, followed by
+// `## Front`, `## Back`, `## Examples`, `## Explanation` and `## Notes` sections
+fn parse_markdown(contents: &str) -> Result<Card> {
+    let (frontmatter, body) = split_frontmatter(contents)?;
+    let mut fields = frontmatter;
+
+    for (name, text) in markdown_sections(body) {
+        if text.is_empty() {
+            continue;
+        }
+        let value = if name == "examples" {
+            Value::Sequence(parse_examples(&text)
+                .into_iter()
+                .map(|(sentence, translation)| {
+                    let mut example = Mapping::new();
+                    example.insert("sentence".into(), sentence.into());
+                    example.insert("translation".into(), translation.into());
+                    Value::Mapping(example)
+                })
+                .collect())
+        } else {
+            Value::String(text)
+        };
+        fields.insert(name.into(), value);
+    }
+
+    Ok(serde_yaml::from_value(Value::Mapping(fields))?)
+}
+
+// Examples are a list of `- sentence - translation` items
+fn parse_examples(text: &str) -> Vec<(&str, &str)> {
+    text.lines()
+        .filter_map(|line| line.trim().strip_prefix("- ").or_else(|| line.trim().strip_prefix("* ")))
+        .map(|item| {
+            let (sentence, translation) = item.split_once(" - ").unwrap_or((item, ""));
+            (sentence.trim(), translation.trim())
+        })
+        .collect()
+}
+
+fn write_examples<'a>(examples: impl IntoIterator<Item = (&'a str, &'a str)>) -> String {
+    examples.into_iter()
+        .map(|(sentence, translation)| match translation {
+            "" => format!("- {}", sentence),
+            translation => format!("- {} - {}", sentence, translation),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+// The body text of a section as the card would have it written
+fn section_text(card: &Card, name: &str) -> String {
+    let text = match name {
+        "front" => card.front.clone(),
+        "back" => card.back.clone(),
+        "explanation" => card.explanation.clone().unwrap_or_default(),
+        "notes" => card.notes.clone().unwrap_or_default(),
+        _ => write_examples(card.examples.iter().flatten()
+            .map(|example| (example.sentence.as_str(), example.translation.as_str()))),
+    };
+    text.trim().to_string()
+}
+
+// The frontmatter is rewritten, but of the body only the sections whose field
+// changed, so other edits to the body survive reviews untouched
+fn write_markdown(card: &Card, existing: &str) -> Result<String> {
+    let (mut frontmatter, body) = split_frontmatter(existing)?;
+
+    let Value::Mapping(fields) = serde_yaml::to_value(card)? else {
+        return Err(eyre!("Card did not serialize to a mapping"));
+    };
+    for (key, value) in fields {
+        if key.as_str().is_some_and(|key| MARKDOWN_SECTIONS.contains(&key)) {
+            continue;
+        }
+        if value.is_null() {
+            frontmatter.remove(&key);
+        } else {
+            frontmatter.insert(key, value);
+        }
+    }
+
+    let mut body = body.to_string();
+    for name in MARKDOWN_SECTIONS {
+        let text = section_text(card, name);
+        // Parsing keeps the last of repeated sections, so that is the one to replace
+        let span = section_spans(&body).into_iter().rev().find(|span| span.name == name);
+        let current = span.as_ref().map(|span| span.text(&body)).unwrap_or_default();
+        let current = if name == "examples" { write_examples(parse_examples(&current)) } else { current };
+        if text == current {
+            continue;
+        }
+
+        match span {
+            Some(span) if text.is_empty() => {
+                let last = span.end == body.len();
+                body.replace_range(span.heading..span.end, "");
+                // The blank line before a removed last section would be left trailing
+                if last {
+                    body.truncate(body.trim_end().len());
+                    body.push('\n');
+                }
+            }
+            Some(span) => {
+                let gap = if span.end < body.len() { "\n\n" } else { "\n" };
+                body.replace_range(span.content..span.end, &format!("\n{}{}", text, gap));
+            }
+            None => {
+                body.truncate(body.trim_end().len());
+                if !body.is_empty() {
+                    body.push_str("\n\n");
+                }
+                body.push_str(&format!("## {}{}\n\n{}\n", name[..1].to_uppercase(), &name[1..], text));
+            }
+        }
+    }
+
+    Ok(format!("---\n{}---\n{}", serde_yaml::to_string(&frontmatter)?, body))
+}
+
+pub fn split_frontmatter(contents: &str) -> Result<(Mapping, &str)> {
+    let Some(rest) = contents.strip_prefix("---\n").or_else(|| contents.strip_prefix("---\r\n")) else {
+        return Ok((Mapping::new(), contents));
+    };
+
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        if line.trim_end() == "---" {
+            let yaml = &rest[..offset];
+            let frontmatter = if yaml.trim().is_empty() {
+                Mapping::new()
+            } else {
+                serde_yaml::from_str(yaml)?
+            };
+            return Ok((frontmatter, &rest[offset + line.len()..]));
+        }
+        offset += line.len();
+    }
+
+    Err(eyre!("Frontmatter is missing its closing ---"))
+}
+
+// A section of a Markdown card's body, by byte offsets: its heading starts at
+// `heading`, its text at `content`, and it runs until `end`
+struct SectionSpan {
+    name: String,
+    heading: usize,
+    content: usize,
+    end: usize,
+}
+
+impl SectionSpan {
+    fn text(&self, body: &str) -> String {
+        body[self.content..self.end].lines().collect::<Vec<_>>().join("\n").trim().to_string()
+    }
+}
+
+// Splits the body on `## ` headings, ignoring headings inside fenced code blocks
+fn section_spans(body: &str) -> Vec<SectionSpan> {
+    let mut sections: Vec<SectionSpan> = Vec::new();
+    let mut in_code = false;
+    let mut offset = 0;
+
+    for line in body.split_inclusive('\n') {
+        if line.trim_start().starts_with("```") {
+            in_code = !in_code;
+        }
+        let heading = line.strip_prefix("## ")
+            .filter(|_| !in_code)
+            .map(|heading| heading.trim().to_lowercase())
+            .filter(|heading| MARKDOWN_SECTIONS.contains(&heading.as_str()));
+
+        if let Some(name) = heading {
+            if let Some(last) = sections.last_mut() {
+                last.end = offset;
+            }
+            sections.push(SectionSpan { name, heading: offset, content: offset + line.len(), end: body.len() });
+        }
+        offset += line.len();
+    }
+    sections
+}
+
+fn markdown_sections(body: &str) -> Vec<(String, String)> {
+    section_spans(body).into_iter()
+        .map(|span| {
+            let text = span.text(body);
+            (span.name, text)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CARD: &str = "---\nnext_review: 10\n---\n# 배\n\n## Front\n\n배\n\n## Back\n\npear\n\n## Notes\n\nAlso a ship\n";
+
+    fn round_trip(card: &Card, existing: &str) -> (String, Card) {
+        let written = CardFormat::Markdown.write(card, None, existing).unwrap();
+        let (parsed, _) = CardFormat::Markdown.parse(&written).unwrap().remove(0);
+        (written, parsed)
+    }
+
+    #[test]
+    fn markdown_cards_keep_edits_to_their_sections() {
+        let (mut card, _) = CardFormat::Markdown.parse(CARD).unwrap().remove(0);
+        card.back = "pear; ship".to_string();
+        let (written, parsed) = round_trip(&card, CARD);
+        assert_eq!(parsed.back, "pear; ship");
+        assert_eq!(parsed.front, "배");
+        assert_eq!(parsed.notes.as_deref(), Some("Also a ship"));
+        assert_eq!(parsed.next_review, Some(10));
+        assert!(written.contains("# 배\n\n## Front\n\n배\n\n## Back\n\npear; ship\n\n## Notes"));
+    }
+
+    #[test]
+    fn notes_added_to_a_markdown_card_get_a_section() {
+        let existing = "## Front\n\n배\n\n## Back\n\npear\n";
+        let (mut card, _) = CardFormat::Markdown.parse(existing).unwrap().remove(0);
+        card.notes = Some("Also a ship".to_string());
+        let (written, parsed) = round_trip(&card, existing);
+        assert_eq!(parsed.notes.as_deref(), Some("Also a ship"));
+        assert!(written.ends_with("## Back\n\npear\n\n## Notes\n\nAlso a ship\n"));
+
+        card.notes = None;
+        let (written, parsed) = round_trip(&card, &written);
+        assert_eq!(parsed.notes, None);
+        assert!(written.ends_with("## Back\n\npear\n"));
+    }
+
+    #[test]
+    fn unchanged_markdown_cards_are_written_back_as_they_were() {
+        let existing = "---\nnext_review: 10\nreversible: true\n---\n## Front\n배\n## Back\n  pear  \n\n## Examples\n* 배를 먹어요 - I eat a pear\n";
+        let (card, _) = CardFormat::Markdown.parse(existing).unwrap().remove(0);
+        let (written, _) = round_trip(&card, existing);
+        assert_eq!(written, existing);
+    }
+}
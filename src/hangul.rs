@@ -0,0 +1,212 @@
+// hangul.rs
+
+// Revised Romanization of each initial consonant, vowel and final consonant,
+// in Unicode order
+const INITIALS: [&str; 19] = [
+    "g", "kk", "n", "d", "tt", "r", "m", "b", "pp", "s", "ss", "", "j", "jj", "ch", "k", "t", "p", "h",
+];
+const VOWELS: [&str; 21] = [
+    "a", "ae", "ya", "yae", "eo", "e", "yeo", "ye", "o", "wa", "wae", "oe", "yo", "u", "wo", "we", "wi",
+    "yu", "eu", "ui", "i",
+];
+// How each final is pronounced at the end of a word
+const FINALS: [&str; 28] = [
+    "", "k", "k", "k", "n", "n", "n", "t", "l", "k", "m", "l", "l", "l", "p", "l", "m", "p", "p", "t",
+    "t", "ng", "t", "t", "k", "t", "p", "t",
+];
+// Each final split into the part that stays and the part carried over to a
+// following syllable that starts with ㅇ. The ㅎ of ㄶ and ㅀ is silent there,
+// so their ㄴ or ㄹ carries over.
+const LIAISON: [(&str, &str); 28] = [
+    ("", ""), ("", "g"), ("", "kk"), ("k", "s"), ("", "n"), ("n", "j"), ("", "n"), ("", "d"), ("", "r"),
+    ("l", "g"), ("l", "m"), ("l", "b"), ("l", "s"), ("l", "t"), ("l", "p"), ("", "r"), ("", "m"),
+    ("", "b"), ("p", "s"), ("", "s"), ("", "ss"), ("ng", ""), ("", "j"), ("", "ch"), ("", "k"),
+    ("", "t"), ("", "p"), ("", ""),
+];
+
+const SYLLABLE_START: u32 = 0xAC00;
+const SYLLABLE_END: u32 = 0xD7A3;
+const INITIAL_IEUNG: usize = 11;
+const INITIAL_NIEUN: usize = 2;
+const INITIAL_RIEUL: usize = 5;
+const INITIAL_MIEUM: usize = 6;
+const INITIAL_HIEUH: usize = 18;
+const FINAL_NIEUN: usize = 4;
+const FINAL_NIEUN_HIEUH: usize = 6;
+const FINAL_RIEUL_HIEUH: usize = 15;
+const FINAL_HIEUH: usize = 27;
+
+/// A Hangul syllable split into indices of its initial, vowel and final
+/// (0 when the syllable has no final).
+#[derive(Clone, Copy)]
+struct Syllable {
+    initial: usize,
+    vowel: usize,
+    final_: usize,
+}
+
+fn syllable(c: char) -> Option<Syllable> {
+    let code = c as u32;
+    if !(SYLLABLE_START..=SYLLABLE_END).contains(&code) {
+        return None;
+    }
+    let index = (code - SYLLABLE_START) as usize;
+    Some(Syllable { initial: index / 588, vowel: index % 588 / 28, final_: index % 28 })
+}
+
+pub fn contains_hangul(text: &str) -> bool {
+    text.chars().any(|c| syllable(c).is_some())
+}
+
+/// Romanizes Hangul in `text` following the Revised Romanization of Korean,
+/// including the common sound changes between syllables of a word (liaison,
+/// nasalization, ㄹ assimilation and aspiration with ㅎ). Anything else is
+/// left as it is.
+pub fn romanize(text: &str) -> String {
+    let mut result = String::new();
+    let mut word = Vec::new();
+
+    for c in text.chars() {
+        match syllable(c) {
+            Some(syllable) => word.push(syllable),
+            None => {
+                romanize_word(&word, &mut result);
+                word.clear();
+                result.push(c);
+            }
+        }
+    }
+    romanize_word(&word, &mut result);
+
+    result
+}
+
+fn romanize_word(word: &[Syllable], result: &mut String) {
+    let mut initial = word.first().map(|syllable| INITIALS[syllable.initial].to_string());
+
+    for (index, syllable) in word.iter().enumerate() {
+        result.push_str(&initial.take().unwrap_or_default());
+        result.push_str(VOWELS[syllable.vowel]);
+
+        match word.get(index + 1) {
+            Some(next) => {
+                let (final_, next_initial) = join(syllable.final_, next.initial);
+                result.push_str(&final_);
+                initial = Some(next_initial);
+            }
+            None => result.push_str(FINALS[syllable.final_]),
+        }
+    }
+}
+
+// How a final and the next syllable's initial are pronounced together
+fn join(final_: usize, initial: usize) -> (String, String) {
+    let sound = FINALS[final_];
+    let initial_text = INITIALS[initial];
+
+    if final_ == 0 {
+        return (String::new(), initial_text.to_string());
+    }
+    if initial == INITIAL_IEUNG {
+        let (kept, carried) = LIAISON[final_];
+        return (kept.to_string(), carried.to_string());
+    }
+
+    // ㅎ merges with a neighbouring plain consonant into an aspirated one
+    let ends_in_hieuh = matches!(final_, FINAL_HIEUH | FINAL_NIEUN_HIEUH | FINAL_RIEUL_HIEUH);
+    if ends_in_hieuh {
+        let aspirated = match initial_text {
+            "g" => Some("k"),
+            "d" => Some("t"),
+            "j" => Some("ch"),
+            _ => None,
+        };
+        if let Some(aspirated) = aspirated {
+            let kept = if final_ == FINAL_HIEUH { "" } else { sound };
+            return (kept.to_string(), aspirated.to_string());
+        }
+        if final_ == FINAL_HIEUH && initial == INITIAL_NIEUN {
+            return ("n".to_string(), "n".to_string());
+        }
+        if final_ == FINAL_HIEUH && initial_text == "s" {
+            return (String::new(), "ss".to_string());
+        }
+    }
+    if initial == INITIAL_HIEUH && matches!(sound, "k" | "t" | "p") {
+        return (String::new(), sound.to_string());
+    }
+
+    match (sound, initial) {
+        ("l", INITIAL_RIEUL) | ("l", INITIAL_NIEUN) => ("l".to_string(), "l".to_string()),
+        _ if final_ == FINAL_NIEUN && initial == INITIAL_RIEUL => ("l".to_string(), "l".to_string()),
+        // ㄹ after any other consonant is read as ㄴ, which then nasalizes the final
+        (_, INITIAL_RIEUL) => (nasalized(sound).to_string(), "n".to_string()),
+        (_, INITIAL_NIEUN | INITIAL_MIEUM) => (nasalized(sound).to_string(), initial_text.to_string()),
+        _ => (sound.to_string(), initial_text.to_string()),
+    }
+}
+
+fn nasalized(sound: &str) -> &str {
+    match sound {
+        "k" => "ng",
+        "t" => "n",
+        "p" => "m",
+        _ => sound,
+    }
+}
+
+/// Splits Hangul syllables into their jamo, so texts can be compared letter
+/// by letter: 갔 becomes ㄱ, ㅏ and ㅆ. Other characters are kept as they are.
+pub fn jamo(text: &str) -> Vec<char> {
+    let mut letters = Vec::new();
+    for c in text.chars() {
+        match syllable(c) {
+            Some(syllable) => {
+                letters.extend(char::from_u32(0x1100 + syllable.initial as u32));
+                letters.extend(char::from_u32(0x1161 + syllable.vowel as u32));
+                if syllable.final_ > 0 {
+                    letters.extend(char::from_u32(0x11A7 + syllable.final_ as u32));
+                }
+            }
+            None => letters.push(c),
+        }
+    }
+    letters
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn romanizes_known_words() {
+        let words = [
+            ("안녕하세요", "annyeonghaseyo"),
+            ("한국어", "hangugeo"),
+            ("독립", "dongnip"),
+            ("설날", "seollal"),
+            ("신라", "silla"),
+            ("백마", "baengma"),
+            ("국물", "gungmul"),
+            ("좋다", "jota"),
+            ("좋아요", "joayo"),
+            ("놓고", "noko"),
+            ("입학", "ipak"),
+            ("읽어", "ilgeo"),
+            ("앉아", "anja"),
+            ("값이", "gapsi"),
+            ("싫어", "sireo"),
+            ("닳아", "dara"),
+            ("많아", "mana"),
+            ("많다", "manta"),
+        ];
+        for (word, expected) in words {
+            assert_eq!(romanize(word), expected, "{}", word);
+        }
+    }
+
+    #[test]
+    fn leaves_other_text_alone() {
+        assert_eq!(romanize("밥 (rice)"), "bap (rice)");
+    }
+}
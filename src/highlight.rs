@@ -0,0 +1,48 @@
+// highlight.rs
+use std::sync::OnceLock;
+use ratatui::style::{Color, Style};
+use ratatui::text::{Line, Span};
+use syntect::easy::HighlightLines;
+use syntect::highlighting::{Theme, ThemeSet};
+use syntect::parsing::SyntaxSet;
+use syntect::util::LinesWithEndings;
+
+// Loading syntax definitions takes a moment, so it happens once, on first use
+static SYNTAXES: OnceLock<SyntaxSet> = OnceLock::new();
+static THEMES: OnceLock<ThemeSet> = OnceLock::new();
+
+const FALLBACK_THEME: &str = "base16-ocean.dark";
+
+/// Highlights `code` for the language named in a fence (`rust`, `sh`, ...),
+/// falling back to plain text for unknown languages.
+pub fn highlight_code(code: &str, language: &str, theme: &str) -> Vec<Line<'static>> {
+    let syntaxes = SYNTAXES.get_or_init(SyntaxSet::load_defaults_newlines);
+    let themes = THEMES.get_or_init(ThemeSet::load_defaults);
+
+    let syntax = syntaxes.find_syntax_by_token(language)
+        .unwrap_or_else(|| syntaxes.find_syntax_plain_text());
+    let theme: &Theme = themes.themes.get(theme)
+        .or_else(|| themes.themes.get(FALLBACK_THEME))
+        .expect("syntect ships with its default themes");
+    let mut highlighter = HighlightLines::new(syntax, theme);
+
+    LinesWithEndings::from(code)
+        .map(|line| {
+            let spans = match highlighter.highlight_line(line, syntaxes) {
+                Ok(ranges) => ranges.into_iter()
+                    .map(|(style, text)| {
+                        let color = Color::Rgb(style.foreground.r, style.foreground.g, style.foreground.b);
+                        Span::styled(text.trim_end_matches(['\n', '\r']).to_string(), Style::new().fg(color))
+                    })
+                    .collect(),
+                Err(_) => vec![Span::raw(line.trim_end_matches(['\n', '\r']).to_string())],
+            };
+            Line::from(spans)
+        })
+        .collect()
+}
+
+/// Whether the text contains a fenced code block.
+pub fn has_code_block(text: &str) -> bool {
+    text.lines().any(|line| line.trim_start().starts_with("```"))
+}
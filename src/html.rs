@@ -0,0 +1,104 @@
+// html.rs
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+use color_eyre::Result;
+use crate::args::CardFilter;
+use crate::model::{Card, Deck};
+use crate::utilities::{current_unix_time, escape_html};
+
+const STYLE: &str = "
+body { font-family: sans-serif; max-width: 60rem; margin: 2rem auto; color: #222; }
+h1 { border-bottom: 2px solid #c90; padding-bottom: 0.2rem; }
+.card { border: 1px solid #999; border-radius: 4px; padding: 1rem; margin: 1rem 0; break-inside: avoid; }
+.front { font-size: 1.6rem; text-align: center; }
+.back { font-size: 1.2rem; text-align: center; margin: 0.8rem 0; }
+.info { display: flex; gap: 0.8rem; }
+.info section { flex: 1; border: 1px solid #ccc; padding: 0.4rem 0.8rem; text-align: center; }
+.info h3 { font-size: 0.8rem; margin: 0 0 0.3rem; text-align: left; color: #666; }
+table.fold { width: 100%; border-collapse: collapse; }
+table.fold td { width: 50%; vertical-align: top; padding: 0.8rem; border-bottom: 1px solid #ccc; break-inside: avoid; }
+table.fold td.front { border-right: 2px dashed #999; vertical-align: middle; }
+table.fold .info { flex-direction: column; }
+@media print { body { margin: 0; max-width: none; } }
+";
+
+pub fn export_html(decks: &[&Deck], output: &Path, fold: bool, only: Option<CardFilter>) -> Result<()> {
+    let current_time = current_unix_time();
+    let title = match decks {
+        [deck] => deck.name.as_str(),
+        _ => "Flashcards",
+    };
+
+    let mut html = String::new();
+    writeln!(html, "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">")?;
+    writeln!(html, "<title>{}</title>\n<style>{}</style>\n</head>\n<body>", escape_html(title), STYLE)?;
+
+    let mut exported = 0;
+    for deck in decks.iter().flat_map(|deck| deck.flatten()) {
+        let cards: Vec<&Card> = deck.cards.iter()
+            .filter(|card| match only {
+                Some(CardFilter::Due) => card.is_due(current_time),
+                Some(CardFilter::Leech) => card.is_leech(),
+                None => true,
+            })
+            .collect();
+        if cards.is_empty() {
+            continue;
+        }
+
+        writeln!(html, "<h1>{}</h1>", escape_html(&deck.name))?;
+        if fold {
+            writeln!(html, "<table class=\"fold\">")?;
+        }
+        for card in cards {
+            write_card(&mut html, card, fold)?;
+            exported += 1;
+        }
+        if fold {
+            writeln!(html, "</table>")?;
+        }
+    }
+
+    writeln!(html, "</body>\n</html>")?;
+    fs::write(output, html)?;
+
+    println!("Exported {} cards to {:?}", exported, output);
+    Ok(())
+}
+
+// Same grouping as the full card view: front, back, then the explanation,
+// examples and notes side by side
+fn write_card(html: &mut String, card: &Card, fold: bool) -> Result<()> {
+    let front = format!("<div class=\"front\">{}</div>", text_html(&card.front));
+    let back = format!("<div class=\"back\">{}</div>", text_html(&card.back));
+
+    let mut info = String::new();
+    if let Some(explanation) = &card.explanation {
+        write!(info, "<section><h3>Explanation</h3>{}</section>", text_html(explanation))?;
+    }
+    if let Some(examples) = card.examples.as_ref().filter(|examples| !examples.is_empty()) {
+        let examples = examples.iter()
+            .map(|example| format!("{} - {}", text_html(&example.sentence), text_html(&example.translation)))
+            .collect::<Vec<_>>()
+            .join("<br>");
+        write!(info, "<section><h3>Examples</h3>{}</section>", examples)?;
+    }
+    if let Some(notes) = &card.notes {
+        write!(info, "<section><h3>Notes</h3>{}</section>", text_html(notes))?;
+    }
+    if !info.is_empty() {
+        info = format!("<div class=\"info\">{}</div>", info);
+    }
+
+    if fold {
+        writeln!(html, "<tr><td class=\"front\">{}</td><td>{}{}</td></tr>", front, back, info)?;
+    } else {
+        writeln!(html, "<div class=\"card\">{}{}{}</div>", front, back, info)?;
+    }
+    Ok(())
+}
+
+fn text_html(text: &str) -> String {
+    escape_html(text).replace('\n', "<br>")
+}
@@ -0,0 +1,54 @@
+// images.rs
+use std::path::{Path, PathBuf};
+use color_eyre::Result;
+use ratatui_image::picker::Picker;
+use ratatui_image::protocol::StatefulProtocol;
+use crate::config::Config;
+
+// Cell size assumed when the terminal does not report its size in pixels
+const FALLBACK_FONT_SIZE: (u16, u16) = (8, 16);
+
+/// The image of the card being shown, encoded for the terminal. Images that
+/// cannot be read keep the error, so it can be shown in place of the image.
+pub struct CardImage {
+    pub path: PathBuf,
+    pub image: Result<EncodedImage, String>,
+}
+
+pub struct EncodedImage {
+    pub protocol: Box<dyn StatefulProtocol>,
+    // Full size of the image in terminal cells
+    pub width: u16,
+    pub height: u16,
+}
+
+/// Picks how images are drawn. Detection queries the terminal, so this must
+/// run after the TUI has started and before any events are read.
+pub fn create_picker(config: &Config) -> Picker {
+    let mut picker = Picker::from_termios().unwrap_or_else(|_| Picker::new(FALLBACK_FONT_SIZE));
+    match config.image_protocol {
+        Some(protocol) => picker.protocol_type = protocol,
+        None => {
+            picker.guess_protocol();
+        }
+    }
+    picker
+}
+
+pub fn load_card_image(picker: &mut Picker, path: &Path) -> CardImage {
+    let image = open_image(path)
+        .map(|image| {
+            let (cell_width, cell_height) = picker.font_size;
+            EncodedImage {
+                width: image.width().div_ceil(cell_width.max(1) as u32).min(u16::MAX as u32) as u16,
+                height: image.height().div_ceil(cell_height.max(1) as u32).min(u16::MAX as u32) as u16,
+                protocol: picker.new_resize_protocol(image),
+            }
+        })
+        .map_err(|e| format!("Could not load {}: {}", path.display(), e));
+    CardImage { path: path.to_path_buf(), image }
+}
+
+fn open_image(path: &Path) -> Result<image::DynamicImage> {
+    Ok(image::ImageReader::open(path)?.with_guessed_format()?.decode()?)
+}
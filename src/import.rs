@@ -0,0 +1,156 @@
+// import.rs
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use color_eyre::{eyre::eyre, Result};
+use crate::format::CardFormat;
+use crate::load::{get_flashcards_dir, read_card_file};
+use crate::model::{Card, ExampleSentence};
+use crate::state::load_states;
+use crate::utilities::{current_unix_time, unique_card_path};
+
+const FIELDS: [&str; 7] = ["front", "back", "notes", "explanation", "example", "translation", "tags"];
+
+pub struct CsvImport<'a> {
+    pub file: &'a Path,
+    pub deck: &'a str,
+    pub map: Option<&'a str>,
+    pub delimiter: Option<char>,
+    pub header: bool,
+    pub single_file: bool,
+    pub dry_run: bool,
+}
+
+pub fn import_csv(options: &CsvImport) -> Result<()> {
+    let deck_dir = get_flashcards_dir().join(options.deck.trim_matches('/'));
+    let delimiter = options.delimiter.unwrap_or_else(|| {
+        match options.file.extension().and_then(|ext| ext.to_str()) {
+            Some("tsv" | "tab") => '\t',
+            _ => ',',
+        }
+    });
+
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(u8::try_from(delimiter).map_err(|_| eyre!("Delimiter must be a single-byte character"))?)
+        .has_headers(options.header)
+        .flexible(true)
+        .from_path(options.file)?;
+    let headers: Vec<String> = if options.header {
+        reader.headers()?.iter().map(|header| header.trim().to_string()).collect()
+    } else {
+        Vec::new()
+    };
+    let columns = parse_column_map(options.map.unwrap_or("front=1,back=2"), &headers)?;
+
+    // Anything already in the deck, keyed the same way new cards are compared
+    let mut existing: HashMap<String, PathBuf> = HashMap::new();
+    if deck_dir.is_dir() {
+        let states = load_states()?;
+        for entry in fs::read_dir(&deck_dir)? {
+            let path = entry?.path();
+            if path.is_file() && CardFormat::from_path(&path).is_some() {
+                for card in read_card_file(&path, &states)? {
+                    existing.insert(duplicate_key(&card.front), path.clone());
+                }
+            }
+        }
+    }
+
+    let now = current_unix_time();
+    let mut new_cards = Vec::new();
+    let mut duplicates = 0;
+    for (line, record) in reader.records().enumerate() {
+        let record = record?;
+        let field = |name: &str| {
+            columns.get(name)
+                .and_then(|&column| record.get(column))
+                .map(str::trim)
+                .filter(|value| !value.is_empty())
+                .map(String::from)
+        };
+
+        let (Some(front), Some(back)) = (field("front"), field("back")) else {
+            eprintln!("Skipping row {}: missing front or back", line + 1 + options.header as usize);
+            continue;
+        };
+        if let Some(path) = existing.get(&duplicate_key(&front)) {
+            println!("Duplicate: {} (already in {:?})", front, path);
+            duplicates += 1;
+            continue;
+        }
+
+        let mut card = Card::new(front, back);
+        card.notes = field("notes");
+        card.explanation = field("explanation");
+        card.tags = field("tags")
+            .map(|tags| tags.split([' ', ',']).filter(|tag| !tag.is_empty()).map(String::from).collect())
+            .unwrap_or_default();
+        card.added = Some(now);
+        if let Some(sentence) = field("example") {
+            card.examples = Some(vec![ExampleSentence {
+                sentence,
+                translation: field("translation").unwrap_or_default(),
+            }]);
+        }
+
+        existing.insert(duplicate_key(&card.front), options.file.to_path_buf());
+        new_cards.push(card);
+    }
+
+    if options.dry_run {
+        for card in &new_cards {
+            println!("New: {} - {}", card.front, card.back);
+        }
+        println!("Would import {} cards into {:?} ({} duplicates skipped)", new_cards.len(), deck_dir, duplicates);
+        return Ok(());
+    }
+
+    fs::create_dir_all(&deck_dir)?;
+    if options.single_file {
+        let stem = options.file.file_stem().map(|stem| stem.to_string_lossy()).unwrap_or_default();
+        let path = unique_card_path(&deck_dir, &stem, "yaml");
+        fs::write(&path, serde_yaml::to_string(&new_cards)?)?;
+    } else {
+        for card in &new_cards {
+            let path = unique_card_path(&deck_dir, &card.front, "yaml");
+            fs::write(&path, serde_yaml::to_string(card)?)?;
+        }
+    }
+
+    println!("Imported {} cards into {:?} ({} duplicates skipped)", new_cards.len(), deck_dir, duplicates);
+    Ok(())
+}
+
+// Parses `front=1,back=2,notes=Notes` into zero-based column indexes, where a
+// column is either a one-based number or a header name
+fn parse_column_map(map: &str, headers: &[String]) -> Result<HashMap<String, usize>> {
+    let mut columns = HashMap::new();
+
+    for pair in map.split(',').filter(|pair| !pair.trim().is_empty()) {
+        let (field, column) = pair.split_once('=')
+            .ok_or_else(|| eyre!("Invalid mapping {:?}, expected field=column", pair))?;
+        let field = field.trim().to_lowercase();
+        let column = column.trim();
+
+        if !FIELDS.contains(&field.as_str()) {
+            return Err(eyre!("Unknown field {:?}, expected one of {}", field, FIELDS.join(", ")));
+        }
+        let index = match column.parse::<usize>() {
+            Ok(0) => return Err(eyre!("Columns are numbered from 1")),
+            Ok(number) => number - 1,
+            Err(_) => headers.iter()
+                .position(|header| header.eq_ignore_ascii_case(column))
+                .ok_or_else(|| eyre!("No column named {:?}", column))?,
+        };
+        columns.insert(field, index);
+    }
+
+    if !columns.contains_key("front") || !columns.contains_key("back") {
+        return Err(eyre!("The mapping must include both front and back"));
+    }
+    Ok(columns)
+}
+
+fn duplicate_key(front: &str) -> String {
+    front.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase()
+}
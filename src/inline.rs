@@ -0,0 +1,249 @@
+// inline.rs
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use color_eyre::Result;
+use crate::format::split_frontmatter;
+use crate::model::Card;
+use crate::state::{load_states, save_states, ReviewState};
+use crate::utilities::{relative_path, stable_hash};
+
+/// Extracts cards written inline in a Markdown note, in the same syntax as
+/// Obsidian's spaced repetition plugin:
+///
+/// - `question :: answer` on one line (`:::` for a reversible card)
+/// - a question, a line with only `?` (`??` for reversible) and an answer
+/// - `==highlighted==` text, which becomes a cloze deletion
+///
+/// Review state for these cards lives in the state store, keyed by an ID
+/// derived from the note's path and the card's question, so the note itself
+/// is never written to. `states` is the state store, read once for all notes.
+pub fn load_inline_cards(path: &Path, contents: &str, states: &BTreeMap<String, ReviewState>) -> Result<Vec<Card>> {
+    let (_, body) = split_frontmatter(contents)?;
+
+    let cards = extract_cards(body);
+    let ids = card_ids(&relative_path(path), &cards);
+    Ok(cards.into_iter()
+        .zip(ids)
+        .map(|(mut card, id)| {
+            if let Some(state) = states.get(&id) {
+                state.apply_to(&mut card);
+            }
+            card.inline_id = Some(id);
+            card.file_path = Some(path.to_path_buf());
+            card
+        })
+        .collect())
+}
+
+/// Moves the stored review state of the cards in a note from `old_path` to
+/// `new_path`, since their IDs depend on the note's path. `contents` is the
+/// note, which is the same at both paths.
+pub fn rekey_inline_state(old_path: &Path, new_path: &Path, contents: &str) -> Result<()> {
+    let (_, body) = split_frontmatter(contents)?;
+    let cards = extract_cards(body);
+    let new_file = relative_path(new_path);
+    let old_ids = card_ids(&relative_path(old_path), &cards);
+    let new_ids = card_ids(&new_file, &cards);
+
+    let mut states = load_states()?;
+    let mut changed = false;
+    for (old_id, new_id) in old_ids.iter().zip(new_ids) {
+        if let Some(mut state) = states.remove(old_id) {
+            state.file = new_file.clone();
+            states.insert(new_id, state);
+            changed = true;
+        }
+    }
+    if changed {
+        save_states(&states)?;
+    }
+    Ok(())
+}
+
+// IDs of the cards of the note at `file`, relative to the flashcards directory
+fn card_ids(file: &str, cards: &[Card]) -> Vec<String> {
+    let mut seen: HashMap<u64, usize> = HashMap::new();
+    cards.iter()
+        .map(|card| {
+            // Repeated questions in one note get an occurrence number to stay distinct
+            let hash = stable_hash(&format!("{}\x1f{}", file, card.front));
+            let occurrence = seen.entry(hash).or_default();
+            let id = match *occurrence {
+                0 => format!("{:016x}", hash),
+                n => format!("{:016x}-{}", hash, n),
+            };
+            *occurrence += 1;
+            id
+        })
+        .collect()
+}
+
+fn extract_cards(body: &str) -> Vec<Card> {
+    let mut cards = Vec::new();
+
+    for paragraph in paragraphs(body) {
+        if let Some(separator) = paragraph.iter().position(|line| matches!(line.trim(), "?" | "??")) {
+            let question = paragraph[..separator].join("\n");
+            let answer = paragraph[separator + 1..].join("\n");
+            if !question.trim().is_empty() && !answer.trim().is_empty() {
+                let mut card = Card::new(question.trim().to_string(), answer.trim().to_string());
+                card.reversible = paragraph[separator].trim() == "??";
+                cards.push(card);
+            }
+            continue;
+        }
+
+        for line in paragraph {
+            if let Some(card) = single_line_card(line) {
+                cards.push(card);
+            } else {
+                cards.extend(cloze_cards(line));
+            }
+        }
+    }
+
+    cards
+}
+
+// Blocks of consecutive non-blank lines, leaving out fenced code
+fn paragraphs(body: &str) -> Vec<Vec<&str>> {
+    let mut paragraphs = vec![Vec::new()];
+    let mut in_code = false;
+
+    for line in body.lines() {
+        if line.trim_start().starts_with("```") {
+            in_code = !in_code;
+            continue;
+        }
+        if in_code {
+            continue;
+        }
+        if line.trim().is_empty() {
+            paragraphs.push(Vec::new());
+        } else if let Some(paragraph) = paragraphs.last_mut() {
+            paragraph.push(line);
+        }
+    }
+
+    paragraphs.retain(|paragraph| !paragraph.is_empty());
+    paragraphs
+}
+
+fn single_line_card(line: &str) -> Option<Card> {
+    let line = strip_list_marker(line);
+    let (separator, reversible) = if line.contains(" ::: ") { (" ::: ", true) } else { (" :: ", false) };
+    let (question, answer) = line.split_once(separator)?;
+    if question.trim().is_empty() || answer.trim().is_empty() {
+        return None;
+    }
+
+    let mut card = Card::new(question.trim().to_string(), answer.trim().to_string());
+    card.reversible = reversible;
+    Some(card)
+}
+
+// One card per highlight: that highlight is hidden on the front, and the back
+// shows the whole line
+fn cloze_cards(line: &str) -> Vec<Card> {
+    let parts = highlight_parts(strip_list_marker(line));
+    let revealed: String = parts.iter().map(|(_, text)| *text).collect();
+
+    parts.iter()
+        .enumerate()
+        .filter(|(_, (highlighted, _))| *highlighted)
+        .map(|(hidden, _)| {
+            let front = parts.iter()
+                .enumerate()
+                .map(|(index, (_, text))| if index == hidden { "[...]" } else { text })
+                .collect::<String>();
+            let mut card = Card::new(front.trim().to_string(), revealed.trim().to_string());
+            card.reversible = false;
+            card
+        })
+        .collect()
+}
+
+// The line split into plain text and `==highlighted==` parts. A highlight
+// needs text right inside both markers, so `a == b and c == d` has none, and
+// code spans are never highlighted.
+fn highlight_parts(line: &str) -> Vec<(bool, &str)> {
+    let mut parts = Vec::new();
+    let mut plain_start = 0;
+    let mut position = 0;
+
+    while position < line.len() {
+        let rest = &line[position..];
+        if rest.starts_with('`') {
+            // A code span ends at the next run of as many backticks
+            let ticks = rest.len() - rest.trim_start_matches('`').len();
+            let fence = &rest[..ticks];
+            position += ticks;
+            if let Some(end) = line[position..].find(fence) {
+                position += end + ticks;
+            }
+            continue;
+        }
+        if let Some(inner) = rest.strip_prefix("==") {
+            let opens = inner.starts_with(|c: char| !c.is_whitespace() && c != '=');
+            if let Some(end) = closing_marker(inner).filter(|_| opens) {
+                parts.push((false, &line[plain_start..position]));
+                parts.push((true, &inner[..end]));
+                position += end + 4;
+                plain_start = position;
+                continue;
+            }
+        }
+        position += rest.chars().next().map_or(1, char::len_utf8);
+    }
+
+    parts.push((false, &line[plain_start..]));
+    parts
+}
+
+// Where the `==` closing a highlight starts in `text`, the part after its opening `==`
+fn closing_marker(text: &str) -> Option<usize> {
+    text.match_indices("==")
+        .map(|(index, _)| index)
+        .take_while(|&index| !text[..index].contains('`'))
+        .find(|&index| index > 0 && !text[..index].ends_with(char::is_whitespace))
+}
+
+fn strip_list_marker(line: &str) -> &str {
+    let line = line.trim();
+    line.strip_prefix("- ")
+        .or_else(|| line.strip_prefix("* "))
+        .unwrap_or(line)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fronts(line: &str) -> Vec<String> {
+        cloze_cards(line).into_iter().map(|card| card.front).collect()
+    }
+
+    #[test]
+    fn highlights_become_cloze_cards() {
+        let cards = cloze_cards("- ==서울== is the capital of ==Korea==");
+        assert_eq!(cards.len(), 2);
+        assert_eq!(cards[0].front, "[...] is the capital of Korea");
+        assert_eq!(cards[1].front, "서울 is the capital of [...]");
+        assert_eq!(cards[0].back, "서울 is the capital of Korea");
+    }
+
+    #[test]
+    fn comparisons_are_not_highlights() {
+        assert!(fronts("if a == b and c == d then").is_empty());
+        assert_eq!(fronts("x ==y== z == w"), vec!["x [...] z == w"]);
+        assert!(fronts("== spaced ==").is_empty());
+        assert!(fronts("unclosed ==marker").is_empty());
+    }
+
+    #[test]
+    fn code_spans_are_skipped() {
+        assert!(fronts("use `a==b==c` here").is_empty());
+        assert!(fronts("use ``x ==y== z`` here").is_empty());
+        assert_eq!(fronts("`a==b` and ==c=="), vec!["`a==b` and [...]"]);
+    }
+}
@@ -0,0 +1,68 @@
+// input.rs
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+/// A single line of editable text with a cursor.
+#[derive(Default)]
+pub struct TextInput {
+    pub value: String,
+    // Cursor position in characters
+    cursor: usize,
+}
+
+impl TextInput {
+    /// Starts editing `value`, with the cursor at its end.
+    pub fn new(value: &str) -> Self {
+        Self { value: value.to_string(), cursor: value.chars().count() }
+    }
+
+    /// Applies an editing key. Returns false for keys the input does not use,
+    /// such as Enter and Esc, so the caller can act on them.
+    pub fn handle_key(&mut self, key: KeyEvent) -> bool {
+        match key.code {
+            KeyCode::Char('u') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.value.drain(..self.byte_index(self.cursor));
+                self.cursor = 0;
+            }
+            KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+                let index = self.byte_index(self.cursor);
+                self.value.insert(index, c);
+                self.cursor += 1;
+            }
+            KeyCode::Backspace if self.cursor > 0 => {
+                self.cursor -= 1;
+                let index = self.byte_index(self.cursor);
+                self.value.remove(index);
+            }
+            KeyCode::Delete if self.cursor < self.value.chars().count() => {
+                let index = self.byte_index(self.cursor);
+                self.value.remove(index);
+            }
+            KeyCode::Left => self.cursor = self.cursor.saturating_sub(1),
+            KeyCode::Right => self.cursor = (self.cursor + 1).min(self.value.chars().count()),
+            KeyCode::Home => self.cursor = 0,
+            KeyCode::End => self.cursor = self.value.chars().count(),
+            KeyCode::Backspace | KeyCode::Delete => {}
+            _ => return false,
+        }
+        true
+    }
+
+    /// Display width of the text before the cursor, where the terminal cursor goes.
+    pub fn cursor_offset(&self) -> u16 {
+        let before: String = self.value.chars().take(self.cursor).collect();
+        unicode_width(&before)
+    }
+
+    /// How far to scroll the text sideways to keep the cursor within `width` columns.
+    pub fn scroll(&self, width: u16) -> u16 {
+        self.cursor_offset().saturating_sub(width.saturating_sub(1))
+    }
+
+    fn byte_index(&self, cursor: usize) -> usize {
+        self.value.char_indices().nth(cursor).map_or(self.value.len(), |(index, _)| index)
+    }
+}
+
+fn unicode_width(text: &str) -> u16 {
+    ratatui::text::Span::raw(text).width() as u16
+}
@@ -1,8 +1,12 @@
 // load.rs
+use std::collections::BTreeMap;
 use std::fs;
 use std::path::{Path, PathBuf};
 use color_eyre::Result;
+use crate::format::{is_markdown_card, CardFormat};
+use crate::inline::load_inline_cards;
 use crate::model::{Card, Deck};
+use crate::state::{load_states, ReviewState};
 use clap::Parser;
 use crate::args::Cli;
 pub fn load_decks() -> Result<Vec<Deck>> {
@@ -13,13 +17,15 @@ pub fn load_decks() -> Result<Vec<Deck>> {
     }
 
     let mut decks = Vec::new();
+    // Read once for every note in every deck
+    let states = load_states()?;
     
     for entry in fs::read_dir(&flashcards_dir)? {
         let entry = entry?;
         let path = entry.path();
         
-        if path.is_dir() {
-            match load_deck_from_directory(&path) {
+        if path.is_dir() && !is_hidden(&path) {
+            match load_deck_from_directory(&path, &states) {
                 Ok(deck) => decks.push(deck),
                 Err(e) => eprintln!("Error loading deck from {:?}: {}", path, e),
             }
@@ -33,7 +39,7 @@ pub fn load_decks() -> Result<Vec<Deck>> {
     Ok(decks)
 }
 
-fn load_deck_from_directory(path: &Path) -> Result<Deck> {
+fn load_deck_from_directory(path: &Path, states: &BTreeMap<String, ReviewState>) -> Result<Deck> {
     if !path.is_dir() {
         return Err(color_eyre::eyre::eyre!("Path is not a directory"));
     }
@@ -53,15 +59,14 @@ fn load_deck_from_directory(path: &Path) -> Result<Deck> {
         let entry = entry?;
         let entry_path = entry.path();
         
-        if entry_path.is_file() && entry_path.extension().map_or(false, |ext| ext == "yaml" || ext == "yml") {
-            let contents = fs::read_to_string(&entry_path)?;
-            let mut card: Card = serde_yaml::from_str(&contents)?;
-            card.file_path = Some(entry_path.clone());
-            // Initialize review data for cards that don't have it
-            card.initialize_review_data();
-            cards.push(card);
-        } else if entry_path.is_dir() {
-            match load_deck_from_directory(&entry_path) {
+        if entry_path.is_file() && CardFormat::from_path(&entry_path).is_some() {
+            for mut card in read_card_file(&entry_path, states)? {
+                // Initialize review data for cards that don't have it
+                card.initialize_review_data();
+                cards.push(card);
+            }
+        } else if entry_path.is_dir() && !is_hidden(&entry_path) {
+            match load_deck_from_directory(&entry_path, states) {
                 Ok(subdeck) => subdecks.push(subdeck),
                 Err(e) => eprintln!("Error loading subdeck from {:?}: {}", entry_path, e),
             }
@@ -75,10 +80,39 @@ fn load_deck_from_directory(path: &Path) -> Result<Deck> {
     })
 }
 
+// Dot-directories hold kkameokda's own data or other tools' (.git, .obsidian)
+fn is_hidden(path: &Path) -> bool {
+    path.file_name().is_some_and(|name| name.to_string_lossy().starts_with('.'))
+}
+
+/// Reads a card file, which holds either a single card or a list of cards.
+pub fn load_cards_from_file(path: &Path) -> Result<Vec<Card>> {
+    read_card_file(path, &load_states()?)
+}
+
+// Like `load_cards_from_file`, with the review state of cards in notes already read
+pub(crate) fn read_card_file(path: &Path, states: &BTreeMap<String, ReviewState>) -> Result<Vec<Card>> {
+    let format = CardFormat::from_path(path)
+        .ok_or_else(|| color_eyre::eyre::eyre!("Unknown card format for {:?}", path))?;
+    let contents = fs::read_to_string(path)?;
+    if format == CardFormat::Markdown && !is_markdown_card(&contents) {
+        return load_inline_cards(path, &contents, states);
+    }
+
+    Ok(format.parse(&contents)?
+        .into_iter()
+        .map(|(mut card, file_index)| {
+            card.file_path = Some(path.to_path_buf());
+            card.file_index = file_index;
+            card
+        })
+        .collect())
+}
+
 pub fn get_flashcards_dir() -> PathBuf {
     let cli = Cli::parse();
-    if cli.directory.is_some() {
-        PathBuf::from(cli.directory.unwrap())
+    if let Some(directory) = cli.directory {
+        PathBuf::from(directory)
     } else {
         dirs::home_dir()
             .expect("Could not find home directory")
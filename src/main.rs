@@ -6,22 +6,71 @@ mod load;
 mod app;
 mod app_runner;
 mod args;
+mod export;
+mod anki;
+mod import;
+mod html;
+mod format;
+mod inline;
+mod state;
+mod config;
+mod markdown;
+mod highlight;
+mod images;
+mod audio;
+mod hangul;
+mod answer;
+mod input;
+mod pinyin;
+mod ruby;
+mod browser;
+mod query;
+mod card_form;
+mod editor;
+mod organize;
+mod trash;
+mod dupes;
 
+use std::fs;
+use std::io::stdout;
+use clap::Parser;
+use ratatui::crossterm::event::{DisableMouseCapture, EnableMouseCapture};
+use ratatui::crossterm::execute;
 use crate::app_runner::run;
-use crate::load::load_decks;
-use crate::utilities::print_deck_structure;
+use crate::config::load_config;
+use crate::args::{Cli, Command, ExportTarget, ImportSource, TrashAction};
+use crate::export::select_decks;
+use crate::import::CsvImport;
+use crate::load::{get_flashcards_dir, load_decks};
+use crate::query::Query;
+use color_eyre::eyre::eyre;
+use crate::organize::{move_path, resolve_path};
+use crate::utilities::{print_deck_structure, relative_path};
 use crate::utilities::print_session_summary;
 
 fn main() -> color_eyre::Result<()> {
+    let cli = Cli::parse();
+
+    match cli.command {
+        None => run_tui(false, cli.query.as_deref()),
+        Some(command) => run_command(command),
+    }
+}
+
+fn run_tui(browse: bool, query: Option<&str>) -> color_eyre::Result<()> {
+    let query = query.map(parse_query).transpose()?;
+    let config = load_config()?;
     let decks = load_decks()?;
     println!("Loaded {} decks:", decks.len());
-    
+
     for deck in &decks {
         print_deck_structure(deck, 0);
     }
-    
+
     let terminal = ratatui::init();
-    let result = run(terminal, decks);
+    execute!(stdout(), EnableMouseCapture)?;
+    let result = run(terminal, decks, config, browse, query);
+    execute!(stdout(), DisableMouseCapture)?;
     ratatui::restore();
 
     if let Ok(app) = &result {
@@ -30,3 +79,81 @@ fn main() -> color_eyre::Result<()> {
 
     result.map(|_| ())
 }
+
+fn run_command(command: Command) -> color_eyre::Result<()> {
+    match command {
+        Command::Browse => run_tui(true, None),
+        Command::List { query, flagged } => {
+            let mut query = parse_query(&query)?;
+            if let Some(flag) = flagged {
+                query.and_flagged(flag);
+            }
+            query::list_cards(&load_decks()?, &query);
+            Ok(())
+        }
+        Command::Mv { source, destination } => {
+            let source = resolve_path(&source);
+            let target = move_path(&source, &resolve_path(&destination))?;
+            println!("Moved {} to {}", relative_path(&source), relative_path(&target));
+            Ok(())
+        }
+        Command::Rm { paths } => {
+            for path in paths {
+                let path = resolve_path(&path);
+                let entry = trash::trash_path(&path)?;
+                println!("Moved {} to the trash, restore it with `kkameokda trash restore {}`", entry.path, entry.id);
+            }
+            Ok(())
+        }
+        Command::Trash { action } => match action.unwrap_or(TrashAction::List) {
+            TrashAction::List => trash::print_trash(),
+            TrashAction::Restore { id } => {
+                let path = trash::restore(&id)?;
+                println!("Restored {}", relative_path(&path));
+                Ok(())
+            }
+            TrashAction::Empty => {
+                println!("Emptied the trash ({} deleted for good)", trash::empty_trash()?);
+                Ok(())
+            }
+        },
+        Command::Dupes { merge } => dupes::report_duplicates(&load_decks()?, merge),
+        Command::ExportLog { format, output } => export::export_log(&load_decks()?, format, output.as_deref()),
+        Command::Export { target } => {
+            let decks = load_decks()?;
+            match target {
+                ExportTarget::Anki { deck, output } => anki::export_anki(&select_decks(&decks, deck.as_deref())?, &output),
+                ExportTarget::Csv { deck, delimiter, schedule, output } => {
+                    export::export_csv(&select_decks(&decks, deck.as_deref())?, output.as_deref(), delimiter, schedule)
+                }
+                ExportTarget::Markdown { deck, schedule, output } => {
+                    export::export_markdown(&select_decks(&decks, deck.as_deref())?, output.as_deref(), schedule)
+                }
+                ExportTarget::Html { deck, fold, only, output } => {
+                    html::export_html(&select_decks(&decks, deck.as_deref())?, &output, fold, only)
+                }
+            }
+        }
+        Command::Import { source } => {
+            fs::create_dir_all(get_flashcards_dir())?;
+            match source {
+                ImportSource::Anki { file, scheduling, history } => anki::import_anki(&file, scheduling, history),
+                ImportSource::Csv { file, deck, map, delimiter, header, single_file, dry_run } => {
+                    import::import_csv(&CsvImport {
+                        file: &file,
+                        deck: &deck,
+                        map: map.as_deref(),
+                        delimiter,
+                        header,
+                        single_file,
+                        dry_run,
+                    })
+                }
+            }
+        }
+    }
+}
+
+fn parse_query(query: &str) -> color_eyre::Result<Query> {
+    Query::parse(query).map_err(|e| eyre!(e.report(query)))
+}
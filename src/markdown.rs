@@ -0,0 +1,187 @@
+// markdown.rs
+use pulldown_cmark::{CodeBlockKind, Event, HeadingLevel, Options, Parser, Tag, TagEnd};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span, Text};
+use crate::highlight::highlight_code;
+
+/// Converts Markdown card text into styled ratatui text. Single line breaks
+/// are kept, since plain card text relies on them for layout. Fenced code
+/// blocks are syntax highlighted with the syntect theme `code_theme`.
+pub fn markdown_text(source: &str, code_theme: &str) -> Text<'static> {
+    let mut renderer = Renderer {
+        code_theme: code_theme.to_string(),
+        ..Renderer::default()
+    };
+    for event in Parser::new_ext(source, Options::ENABLE_STRIKETHROUGH) {
+        renderer.handle(event);
+    }
+    renderer.finish()
+}
+
+#[derive(Default)]
+struct Renderer {
+    lines: Vec<Line<'static>>,
+    current: Vec<Span<'static>>,
+    styles: Vec<Style>,
+    // One entry per open list: the next item number, or None when unordered
+    lists: Vec<Option<u64>>,
+    links: Vec<String>,
+    // Language and text of the fenced code block being read
+    code: Option<(String, String)>,
+    code_theme: String,
+}
+
+impl Renderer {
+    fn style(&self) -> Style {
+        self.styles.last().copied().unwrap_or_default()
+    }
+
+    fn push_style(&mut self, modifier: Style) {
+        self.styles.push(self.style().patch(modifier));
+    }
+
+    fn push_text(&mut self, text: &str, style: Style) {
+        self.current.push(Span::styled(text.to_string(), style));
+    }
+
+    fn end_line(&mut self) {
+        self.lines.push(Line::from(std::mem::take(&mut self.current)));
+    }
+
+    // Starts a new block, leaving one blank line after the previous one
+    fn end_block(&mut self) {
+        if !self.current.is_empty() {
+            self.end_line();
+        }
+        if self.lists.is_empty() && self.lines.last().is_some_and(|line| line.width() > 0) {
+            self.lines.push(Line::default());
+        }
+    }
+
+    fn handle(&mut self, event: Event) {
+        match event {
+            Event::Start(tag) => self.start(tag),
+            Event::End(tag) => self.end(tag),
+            Event::Text(text) if self.code.is_some() => {
+                if let Some((_, code)) = &mut self.code {
+                    code.push_str(&text);
+                }
+            }
+            Event::Text(text) => {
+                for (index, piece) in text.split('\n').enumerate() {
+                    if index > 0 {
+                        self.end_line();
+                    }
+                    if !piece.is_empty() {
+                        self.push_text(piece, self.style());
+                    }
+                }
+            }
+            Event::Code(code) => self.push_text(&code, self.style().fg(Color::Cyan)),
+            Event::SoftBreak | Event::HardBreak => self.end_line(),
+            Event::Rule => {
+                self.end_block();
+                self.lines.push(Line::from("───"));
+            }
+            Event::TaskListMarker(done) => self.push_text(if done { "[x] " } else { "[ ] " }, self.style()),
+            Event::Html(html) | Event::InlineHtml(html) => self.push_text(&html, self.style()),
+            _ => {}
+        }
+    }
+
+    fn start(&mut self, tag: Tag) {
+        match tag {
+            Tag::Heading { level, .. } => {
+                self.end_block();
+                let style = match level {
+                    HeadingLevel::H1 => Style::new().add_modifier(Modifier::BOLD | Modifier::UNDERLINED),
+                    _ => Style::new().add_modifier(Modifier::BOLD),
+                };
+                self.push_style(style.fg(Color::Yellow));
+            }
+            Tag::Emphasis => self.push_style(Style::new().add_modifier(Modifier::ITALIC)),
+            Tag::Strong => self.push_style(Style::new().add_modifier(Modifier::BOLD)),
+            Tag::Strikethrough => self.push_style(Style::new().add_modifier(Modifier::CROSSED_OUT)),
+            Tag::Link { dest_url, .. } => {
+                self.links.push(dest_url.to_string());
+                self.push_style(Style::new().fg(Color::Blue).add_modifier(Modifier::UNDERLINED));
+            }
+            Tag::CodeBlock(kind) => {
+                self.end_block();
+                let language = match kind {
+                    CodeBlockKind::Fenced(info) => info.split_whitespace().next().unwrap_or_default().to_string(),
+                    CodeBlockKind::Indented => String::new(),
+                };
+                self.code = Some((language, String::new()));
+            }
+            Tag::BlockQuote(_) => self.push_style(Style::new().add_modifier(Modifier::ITALIC)),
+            Tag::List(start) => {
+                if !self.current.is_empty() {
+                    self.end_line();
+                }
+                if self.lists.is_empty() {
+                    self.end_block();
+                }
+                self.lists.push(start);
+            }
+            Tag::Item => {
+                if !self.current.is_empty() {
+                    self.end_line();
+                }
+                let indent = "  ".repeat(self.lists.len().saturating_sub(1));
+                let marker = match self.lists.last_mut() {
+                    Some(Some(number)) => {
+                        *number += 1;
+                        format!("{}{}. ", indent, *number - 1)
+                    }
+                    _ => format!("{}• ", indent),
+                };
+                self.push_text(&marker, Style::new().fg(Color::Yellow));
+            }
+            _ => {}
+        }
+    }
+
+    fn end(&mut self, tag: TagEnd) {
+        match tag {
+            TagEnd::Paragraph => self.end_block(),
+            TagEnd::Heading(_) => {
+                self.styles.pop();
+                self.end_block();
+            }
+            TagEnd::Emphasis | TagEnd::Strong | TagEnd::Strikethrough | TagEnd::BlockQuote(_) => {
+                self.styles.pop();
+            }
+            TagEnd::Link => {
+                self.styles.pop();
+                if let Some(url) = self.links.pop() {
+                    self.push_text(&format!(" ({})", url), Style::new().fg(Color::DarkGray));
+                }
+            }
+            TagEnd::CodeBlock => {
+                if let Some((language, code)) = self.code.take() {
+                    self.lines.extend(highlight_code(&code, &language, &self.code_theme));
+                }
+                self.end_block();
+            }
+            TagEnd::Item if !self.current.is_empty() => self.end_line(),
+            TagEnd::List(_) => {
+                self.lists.pop();
+                if self.lists.is_empty() {
+                    self.end_block();
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn finish(mut self) -> Text<'static> {
+        if !self.current.is_empty() {
+            self.end_line();
+        }
+        while self.lines.last().is_some_and(|line| line.width() == 0) {
+            self.lines.pop();
+        }
+        Text::from(self.lines)
+    }
+}
@@ -1,9 +1,15 @@
 // model.rs
-use crate::utilities::current_unix_time;
+use crate::format::CardFormat;
+use crate::state::save_card_state;
+use crate::utilities::{current_unix_time, relative_path};
 use std::fs;
+use clap::ValueEnum;
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
-use color_eyre::Result;
+use color_eyre::{eyre::eyre, Result};
+
+/// Cards forgotten this many times count as leeches, as in Anki.
+pub const LEECH_THRESHOLD: usize = 8;
 
 #[derive(Deserialize, Serialize, Clone)]
 pub struct Deck {
@@ -27,8 +33,158 @@ pub struct Card {
     pub ease_factor: Option<u64>,
     #[serde(default = "Card::default_reversible")]
     pub reversible: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub image: Option<String>, // Relative to the card's file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub audio: Option<String>, // Relative to the card's file
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub suspended: bool, // Left out of reviews until unsuspended
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub flag: Option<Flag>, // Marked for later attention
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub added: Option<u64>, // UNIX timestamp of when the card was created
     #[serde(skip)]  // Don't deserialize from YAML
     pub file_path: Option<PathBuf>,
+    #[serde(skip)]  // Position within a file holding a list of cards
+    pub file_index: Option<usize>,
+    #[serde(skip)]  // Set for cards extracted from notes, whose review state is stored separately
+    pub inline_id: Option<String>,
+}
+
+/// Where a card sits in the deck tree: the subdeck indices leading to its
+/// deck, and its index among that deck's cards.
+#[derive(Clone, PartialEq)]
+pub struct CardLocation {
+    pub deck: Vec<usize>,
+    pub index: usize,
+}
+
+impl Deck {
+    /// Returns this deck followed by all of its subdecks, depth first.
+    pub fn flatten(&self) -> Vec<&Deck> {
+        let mut decks = vec![self];
+        for subdeck in &self.subdecks {
+            decks.extend(subdeck.flatten());
+        }
+        decks
+    }
+
+    /// Every card in `decks` and their subdecks, with its location and deck.
+    pub fn locate_cards(decks: &[Deck]) -> Vec<(CardLocation, &Card, &Deck)> {
+        fn visit<'a>(deck: &'a Deck, path: &mut Vec<usize>, cards: &mut Vec<(CardLocation, &'a Card, &'a Deck)>) {
+            for (index, card) in deck.cards.iter().enumerate() {
+                cards.push((CardLocation { deck: path.clone(), index }, card, deck));
+            }
+            for (index, subdeck) in deck.subdecks.iter().enumerate() {
+                path.push(index);
+                visit(subdeck, path, cards);
+                path.pop();
+            }
+        }
+
+        let mut cards = Vec::new();
+        for (index, deck) in decks.iter().enumerate() {
+            visit(deck, &mut vec![index], &mut cards);
+        }
+        cards
+    }
+
+    /// Every deck in `decks` and their subdecks, with the indices leading to it.
+    pub fn locate_decks(decks: &[Deck]) -> Vec<(Vec<usize>, &Deck)> {
+        fn visit<'a>(deck: &'a Deck, path: &mut Vec<usize>, found: &mut Vec<(Vec<usize>, &'a Deck)>) {
+            found.push((path.clone(), deck));
+            for (index, subdeck) in deck.subdecks.iter().enumerate() {
+                path.push(index);
+                visit(subdeck, path, found);
+                path.pop();
+            }
+        }
+
+        let mut found = Vec::new();
+        for (index, deck) in decks.iter().enumerate() {
+            visit(deck, &mut vec![index], &mut found);
+        }
+        found
+    }
+
+    pub fn deck_at_mut<'a>(decks: &'a mut [Deck], path: &[usize]) -> Option<&'a mut Deck> {
+        let (first, rest) = path.split_first()?;
+        let mut deck = decks.get_mut(*first)?;
+        for index in rest {
+            deck = deck.subdecks.get_mut(*index)?;
+        }
+        Some(deck)
+    }
+
+    pub fn card_at<'a>(decks: &'a [Deck], location: &CardLocation) -> Option<&'a Card> {
+        let (first, rest) = location.deck.split_first()?;
+        let mut deck = decks.get(*first)?;
+        for index in rest {
+            deck = deck.subdecks.get(*index)?;
+        }
+        deck.cards.get(location.index)
+    }
+
+    pub fn card_at_mut<'a>(decks: &'a mut [Deck], location: &CardLocation) -> Option<&'a mut Card> {
+        Deck::deck_at_mut(decks, &location.deck)?.cards.get_mut(location.index)
+    }
+}
+
+/// Colored flags for marking cards that need attention, as in Anki. What
+/// each color means is up to the user.
+#[derive(Deserialize, Serialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Flag {
+    Red,
+    Orange,
+    Green,
+    Blue,
+    Purple,
+}
+
+impl Flag {
+    pub const ALL: [Flag; 5] = [Flag::Red, Flag::Orange, Flag::Green, Flag::Blue, Flag::Purple];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Flag::Red => "red",
+            Flag::Orange => "orange",
+            Flag::Green => "green",
+            Flag::Blue => "blue",
+            Flag::Purple => "purple",
+        }
+    }
+}
+
+/// The text fields of a card that can be edited during a review.
+#[derive(Clone, Copy, PartialEq)]
+pub enum CardField {
+    Front,
+    Back,
+    Notes,
+    Explanation,
+}
+
+impl CardField {
+    pub fn name(self) -> &'static str {
+        match self {
+            CardField::Front => "front",
+            CardField::Back => "back",
+            CardField::Notes => "notes",
+            CardField::Explanation => "explanation",
+        }
+    }
+
+    pub fn next(self) -> Self {
+        match self {
+            CardField::Front => CardField::Back,
+            CardField::Back => CardField::Notes,
+            CardField::Notes => CardField::Explanation,
+            CardField::Explanation => CardField::Front,
+        }
+    }
 }
 
 #[derive(Deserialize, Serialize, Clone)]
@@ -39,22 +195,80 @@ pub struct ExampleSentence {
 
 #[derive(Deserialize, Serialize, Clone)]
 pub struct ReviewHistory {
-     date: u64, // UNIX timestamp
-     remembered: bool,
+     pub date: u64, // UNIX timestamp
+     pub remembered: bool,
+     #[serde(default, skip_serializing_if = "Option::is_none")]
+     pub reversed: Option<bool>,
+     #[serde(default, skip_serializing_if = "Option::is_none")]
+     pub interval: Option<u64>, // Seconds until the next review
+     #[serde(default, skip_serializing_if = "Option::is_none")]
+     pub partial: Option<bool>, // Remembered with mistakes, which earns a shorter interval
  }
 
  impl Card {
-    fn default_reversible() -> bool {
+    pub fn new(front: String, back: String) -> Self {
+        Self {
+            front,
+            back,
+            notes: None,
+            examples: None,
+            explanation: None,
+            history: None,
+            next_review: None,
+            ease_factor: None,
+            reversible: Card::default_reversible(),
+            image: None,
+            audio: None,
+            suspended: false,
+            flag: None,
+            tags: Vec::new(),
+            added: None,
+            file_path: None,
+            file_index: None,
+            inline_id: None,
+        }
+    }
+
+    pub fn default_reversible() -> bool {
         true
     }
 
-    pub fn calculate_next_review(&mut self, current_time: u64, remembered: bool) -> Result<u64> {
+    /// Location of the card's image, resolved against the directory of its file.
+    pub fn image_path(&self) -> Option<PathBuf> {
+        self.image.as_deref().map(|image| self.resolve_path(image))
+    }
+
+    /// Location of the card's audio, resolved against the directory of its file.
+    pub fn audio_path(&self) -> Option<PathBuf> {
+        self.audio.as_deref().map(|audio| self.resolve_path(audio))
+    }
+
+    fn resolve_path(&self, relative: &str) -> PathBuf {
+        let directory = self.file_path.as_ref().and_then(|path| path.parent());
+        directory.map_or_else(|| PathBuf::from(relative), |directory| directory.join(relative))
+    }
+
+    pub fn calculate_next_review(&mut self, current_time: u64, remembered: bool, reversed: bool) -> Result<u64> {
+        let growth = if remembered { Some(1.5) } else { None };
+        self.schedule_review(current_time, growth, reversed, false)
+    }
+
+    /// Schedules a card that was remembered with mistakes, such as a typed
+    /// answer that was nearly right. Its interval grows more slowly than for a
+    /// correct answer, like Anki's "Hard".
+    pub fn calculate_partial_review(&mut self, current_time: u64, reversed: bool) -> Result<u64> {
+        self.schedule_review(current_time, Some(1.2), reversed, true)
+    }
+
+    // `growth` multiplies the interval of a remembered card; None starts it over
+    fn schedule_review(&mut self, current_time: u64, growth: Option<f64>, reversed: bool, partial: bool) -> Result<u64> {
         let base_interval = 24 * 60 * 60; // 1 day in seconds
         let max_interval = 180 * 24 * 60 * 60; // 6 months in seconds
         let random_factor = (0.8 + (rand::random::<f64>() * 0.4)) as u64;
+        let remembered = growth.is_some();
         
-        if remembered {
-            let new_ease = (self.ease_factor.unwrap_or(base_interval) as f64 * 1.5) as u64;
+        if let Some(growth) = growth {
+            let new_ease = (self.ease_factor.unwrap_or(base_interval) as f64 * growth) as u64;
             self.ease_factor = Some(new_ease.min(max_interval));
             self.next_review = Some(current_time + self.ease_factor.unwrap() + random_factor);
         } else {
@@ -70,18 +284,117 @@ pub struct ReviewHistory {
             history.push(ReviewHistory {
                 date: current_time,
                 remembered,
+                reversed: Some(reversed),
+                interval: self.next_review.map(|next| next.saturating_sub(current_time)),
+                partial: partial.then_some(true),
             });
         }
 
         // Write changes back to file
-        if let Some(path) = &self.file_path {
-            let yaml = serde_yaml::to_string(&self)?;
-            fs::write(path, yaml)?;
-        }
+        self.save()?;
         
         Ok(self.next_review.expect("Could not set next review."))
     }
 
+    /// Writes the card back to its file in the file's own format. Cards that
+    /// share a file with others only replace their own entry, so the rest of
+    /// the file is left as it is on disk. Cards extracted from notes only
+    /// update the state store.
+    pub fn save(&self) -> Result<()> {
+        let Some(path) = &self.file_path else {
+            return Ok(());
+        };
+        if let Some(id) = &self.inline_id {
+            return save_card_state(id, &relative_path(path), self);
+        }
+
+        let format = CardFormat::from_path(path)
+            .ok_or_else(|| eyre!("Unknown card format for {:?}", path))?;
+        let existing = if path.exists() { fs::read_to_string(path)? } else { String::new() };
+        fs::write(path, format.write(self, self.file_index, &existing)?)?;
+        Ok(())
+    }
+
+    pub fn field(&self, field: CardField) -> &str {
+        match field {
+            CardField::Front => &self.front,
+            CardField::Back => &self.back,
+            CardField::Notes => self.notes.as_deref().unwrap_or_default(),
+            CardField::Explanation => self.explanation.as_deref().unwrap_or_default(),
+        }
+    }
+
+    /// Sets a field, removing optional fields that are left empty.
+    pub fn set_field(&mut self, field: CardField, value: String) {
+        let optional = Some(value.clone()).filter(|value| !value.trim().is_empty());
+        match field {
+            CardField::Front => self.front = value,
+            CardField::Back => self.back = value,
+            CardField::Notes => self.notes = optional,
+            CardField::Explanation => self.explanation = optional,
+        }
+    }
+
+    /// Whether `other` is a copy of this card, even if its fields or review
+    /// state have changed since. Cards are told apart by where they are stored.
+    pub fn is_same_card(&self, other: &Card) -> bool {
+        if self.file_path.is_none() {
+            return self.front == other.front && self.back == other.back;
+        }
+        self.file_path == other.file_path && self.file_index == other.file_index && self.inline_id == other.inline_id
+    }
+
+    pub fn is_due(&self, current_time: u64) -> bool {
+        !self.suspended && self.next_review < Some(current_time)
+    }
+
+    /// Removes the card from its file, deleting the file when nothing else is
+    /// left in it. Cards inside notes can only be removed by editing the note.
+    pub fn delete(&self) -> Result<()> {
+        let Some(path) = &self.file_path else {
+            return Ok(());
+        };
+        if self.inline_id.is_some() {
+            return Err(eyre!("Cards inside notes are deleted by editing {}", path.display()));
+        }
+
+        let format = CardFormat::from_path(path)
+            .ok_or_else(|| eyre!("Unknown card format for {:?}", path))?;
+        let remaining = match self.file_index {
+            Some(index) => format.remove(index, &fs::read_to_string(path)?)?,
+            None => None,
+        };
+        match remaining {
+            Some(contents) => fs::write(path, contents)?,
+            None => fs::remove_file(path)?,
+        }
+        Ok(())
+    }
+
+    /// When the card was created: its `added` date, or failing that its first
+    /// review or the last change to its file.
+    pub fn added_date(&self) -> Option<u64> {
+        self.added
+            .or_else(|| self.history.as_deref()?.first().map(|review| review.date))
+            .or_else(|| {
+                let modified = fs::metadata(self.file_path.as_ref()?).ok()?.modified().ok()?;
+                Some(modified.duration_since(std::time::UNIX_EPOCH).ok()?.as_secs())
+            })
+    }
+
+    /// Number of times the card was forgotten.
+    pub fn lapses(&self) -> usize {
+        self.history.as_deref()
+            .unwrap_or_default()
+            .iter()
+            .filter(|review| !review.remembered)
+            .count()
+    }
+
+    pub fn is_leech(&self) -> bool {
+        self.lapses() >= LEECH_THRESHOLD
+    }
+
     pub fn initialize_review_data(&mut self) {
         if self.next_review.is_none() {
             self.next_review = Some(current_unix_time() - 1);
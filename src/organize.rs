@@ -0,0 +1,136 @@
+// organize.rs
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use color_eyre::{eyre::eyre, Result};
+use crate::format::{is_markdown_card, CardFormat};
+use crate::inline::rekey_inline_state;
+use crate::load::get_flashcards_dir;
+use crate::model::Card;
+use crate::utilities::{relative_path, unique_card_path};
+
+/// A card file or deck directory given on the command line, relative to the
+/// flashcards directory unless it is absolute.
+pub fn resolve_path(path: &Path) -> PathBuf {
+    get_flashcards_dir().join(path)
+}
+
+/// Checks that `path` is a card file or deck inside the flashcards directory,
+/// rather than the directory itself or data kkameokda does not load.
+pub fn check_managed(path: &Path) -> Result<()> {
+    let flashcards_dir = get_flashcards_dir();
+    let relative = path.strip_prefix(&flashcards_dir)
+        .map_err(|_| eyre!("{} is outside the flashcards directory {}", path.display(), flashcards_dir.display()))?;
+    if relative.as_os_str().is_empty() {
+        return Err(eyre!("This is the flashcards directory itself, not a deck"));
+    }
+    let hidden = relative.components().any(|component| match component {
+        Component::Normal(name) => name.to_string_lossy().starts_with('.'),
+        _ => true,
+    });
+    if hidden {
+        return Err(eyre!("{} is not a card file or deck", relative.display()));
+    }
+    Ok(())
+}
+
+/// Moves a card file or deck directory, like `mv`: into `destination` when it
+/// is an existing deck, or otherwise to that path. The review state stored for
+/// cards inside notes follows them to their new path.
+pub fn move_path(source: &Path, destination: &Path) -> Result<PathBuf> {
+    check_managed(source)?;
+    if !source.exists() {
+        return Err(eyre!("{} does not exist", relative_path(source)));
+    }
+    let target = match source.file_name() {
+        Some(name) if destination.is_dir() => destination.join(name),
+        _ => destination.to_path_buf(),
+    };
+    check_managed(&target)?;
+    if target.exists() {
+        return Err(eyre!("{} already exists", relative_path(&target)));
+    }
+    if target.starts_with(source) {
+        return Err(eyre!("Cannot move {} into itself", relative_path(source)));
+    }
+    if source.is_file() {
+        // Cards are only loaded from deck directories, in a format given by their extension
+        if target.parent() == Some(get_flashcards_dir().as_path()) {
+            return Err(eyre!("Card files belong in a deck directory, not directly in the flashcards directory"));
+        }
+        if target.extension() != source.extension() {
+            return Err(eyre!("{} would change the card's format; keep the .{} extension",
+                relative_path(&target), source.extension().unwrap_or_default().to_string_lossy()));
+        }
+    }
+
+    let notes = find_notes(source)?;
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::rename(source, &target)?;
+    rekey_notes(source, &target, &notes)?;
+    Ok(target)
+}
+
+/// Moves a card to the deck directory `deck_dir`. A card that shares its file
+/// with others is taken out into a file of its own.
+pub fn move_card(card: &Card, deck_dir: &Path) -> Result<PathBuf> {
+    let Some(path) = &card.file_path else {
+        return Err(eyre!("This card is not stored in a file"));
+    };
+    if card.inline_id.is_some() {
+        return Err(eyre!("Cards inside notes move with their note: kkameokda mv {} <deck>", relative_path(path)));
+    }
+    if path.parent() == Some(deck_dir) {
+        return Err(eyre!("The card is already in that deck"));
+    }
+    if card.file_index.is_none() {
+        return move_path(path, deck_dir);
+    }
+
+    let target = write_card_file(card, path, deck_dir)?;
+    card.delete()?;
+    Ok(target)
+}
+
+/// Writes `card`, which was read from `path`, to a new file of the same format
+/// in `dir`.
+pub fn write_card_file(card: &Card, path: &Path, dir: &Path) -> Result<PathBuf> {
+    let format = CardFormat::from_path(path)
+        .ok_or_else(|| eyre!("Unknown card format for {:?}", path))?;
+    let extension = path.extension().unwrap_or_default().to_string_lossy();
+    fs::create_dir_all(dir)?;
+    let target = unique_card_path(dir, &card.front, &extension);
+    fs::write(&target, format.write(card, None, "")?)?;
+    Ok(target)
+}
+
+/// Markdown notes holding inline cards at or below `path`.
+pub fn find_notes(path: &Path) -> Result<Vec<PathBuf>> {
+    if path.is_file() {
+        let is_note = CardFormat::from_path(path) == Some(CardFormat::Markdown)
+            && !is_markdown_card(&fs::read_to_string(path)?);
+        return Ok(if is_note { vec![path.to_path_buf()] } else { Vec::new() });
+    }
+
+    let mut notes = Vec::new();
+    if path.is_dir() {
+        for entry in fs::read_dir(path)? {
+            notes.extend(find_notes(&entry?.path())?);
+        }
+    }
+    Ok(notes)
+}
+
+/// Moves the review state of `notes`, which were at or below `source`, to
+/// where they are below `target` now.
+pub fn rekey_notes(source: &Path, target: &Path, notes: &[PathBuf]) -> Result<()> {
+    for note in notes {
+        let new_path = match note.strip_prefix(source) {
+            Ok(rest) if !rest.as_os_str().is_empty() => target.join(rest),
+            _ => target.to_path_buf(),
+        };
+        rekey_inline_state(note, &new_path, &fs::read_to_string(&new_path)?)?;
+    }
+    Ok(())
+}
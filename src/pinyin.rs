@@ -0,0 +1,144 @@
+// pinyin.rs
+
+const INITIALS: [&str; 23] = [
+    "zh", "ch", "sh", "b", "p", "m", "f", "d", "t", "n", "l", "g", "k", "h", "j", "q", "x", "r", "z", "c",
+    "s", "y", "w",
+];
+const FINALS: [&str; 38] = [
+    "a", "o", "e", "ai", "ei", "ao", "ou", "an", "en", "ang", "eng", "ong", "er", "i", "ia", "ie", "iao",
+    "iu", "iou", "ian", "in", "iang", "ing", "iong", "u", "ua", "uo", "uai", "ui", "uei", "uan", "un",
+    "uen", "uang", "ueng", "ü", "üe", "ue",
+];
+
+/// Converts numbered pinyin such as `zhong1guo2` or `lv4` into tone marks
+/// (`zhōngguó`, `lǜ`). Only words made entirely of valid pinyin syllables
+/// are converted, so things like `mp3`, `area51`, `go1` or `A1` are left alone.
+pub fn tone_marks(text: &str) -> String {
+    let mut result = String::new();
+    let mut word = String::new();
+
+    for c in text.chars() {
+        if c.is_ascii_alphanumeric() || c == 'ü' || c == 'Ü' || c == ':' {
+            word.push(c);
+        } else {
+            result.push_str(&convert_word(&word));
+            word.clear();
+            result.push(c);
+        }
+    }
+    result.push_str(&convert_word(&word));
+
+    result
+}
+
+fn convert_word(word: &str) -> String {
+    // Pinyin is written in lowercase, apart from capitalized names
+    if !word.ends_with(|c: char| c.is_ascii_digit()) || !word.chars().any(char::is_lowercase) {
+        return word.to_string();
+    }
+
+    let mut converted = String::new();
+    let mut syllable = String::new();
+    for c in word.chars() {
+        if let Some(tone) = c.to_digit(10) {
+            match mark_syllable(&syllable, tone) {
+                Some(marked) => converted.push_str(&marked),
+                None => return word.to_string(),
+            }
+            syllable.clear();
+        } else {
+            syllable.push(c);
+        }
+    }
+    converted
+}
+
+// Returns None when `syllable` is not pinyin or `tone` is not a tone number
+fn mark_syllable(syllable: &str, tone: u32) -> Option<String> {
+    if !(1..=5).contains(&tone) {
+        return None;
+    }
+    // `v` and `u:` are the usual ways of typing ü
+    let syllable = syllable.replace("u:", "ü").replace("U:", "Ü").replace('v', "ü").replace('V', "Ü");
+    let lower = syllable.to_lowercase();
+    let (initial, final_) = INITIALS.iter()
+        .find_map(|initial| Some((*initial, lower.strip_prefix(initial).filter(|rest| !rest.is_empty())?)))
+        .unwrap_or(("", &lower));
+    if !FINALS.contains(&final_) || !can_combine(initial, final_) {
+        return None;
+    }
+    if tone == 5 {
+        return Some(syllable);
+    }
+
+    // The mark goes on a or e, on the o of ou, and otherwise on the last vowel
+    let chars: Vec<char> = syllable.chars().collect();
+    let is_vowel = |c: &char| "aeiouü".contains(c.to_ascii_lowercase()) || *c == 'Ü';
+    let position = ["a", "e", "ou"].iter()
+        .find_map(|vowel| lower.find(vowel).map(|byte| lower[..byte].chars().count()))
+        .or_else(|| chars.iter().rposition(is_vowel))?;
+
+    Some(chars.iter()
+        .enumerate()
+        .map(|(index, &c)| if index == position { with_tone(c, tone) } else { c })
+        .collect())
+}
+
+// Rules out look-alikes such as `go` or `hi`, whose initial never comes
+// before that final in Mandarin
+fn can_combine(initial: &str, final_: &str) -> bool {
+    let i_final = final_.starts_with('i');
+    let u_umlaut = final_.starts_with('ü');
+    match initial {
+        "" => matches!(final_, "a" | "o" | "e" | "ai" | "ei" | "ao" | "ou" | "an" | "en" | "ang" | "eng" | "er"),
+        _ if final_ == "er" => false,
+        "g" | "k" | "h" => !i_final && !u_umlaut && final_ != "o",
+        // ü is written as u after j, q, x and y
+        "j" | "q" | "x" => i_final || matches!(final_, "u" | "ue" | "uan" | "un" | "ü" | "üe"),
+        "zh" | "ch" | "sh" | "r" | "z" | "c" | "s" => (!i_final || final_ == "i") && !u_umlaut,
+        "y" => matches!(final_, "a" | "o" | "e" | "ao" | "ou" | "an" | "in" | "ang" | "ing" | "ong" | "i" | "u" | "ue" | "uan" | "un"),
+        "w" => matches!(final_, "a" | "o" | "ai" | "ei" | "an" | "en" | "ang" | "eng" | "u"),
+        "b" | "p" | "m" | "f" => !u_umlaut && (!final_.starts_with('u') || final_ == "u"),
+        _ => (!u_umlaut || matches!(initial, "n" | "l")) && (final_ != "o" || initial == "l"),
+    }
+}
+
+fn with_tone(vowel: char, tone: u32) -> char {
+    let marks = match vowel {
+        'a' => "āáǎà",
+        'e' => "ēéěè",
+        'i' => "īíǐì",
+        'o' => "ōóǒò",
+        'u' => "ūúǔù",
+        'ü' => "ǖǘǚǜ",
+        'A' => "ĀÁǍÀ",
+        'E' => "ĒÉĚÈ",
+        'I' => "ĪÍǏÌ",
+        'O' => "ŌÓǑÒ",
+        'U' => "ŪÚǓÙ",
+        'Ü' => "ǕǗǙǛ",
+        _ => return vowel,
+    };
+    marks.chars().nth(tone as usize - 1).unwrap_or(vowel)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_numbered_pinyin() {
+        assert_eq!(tone_marks("zhong1guo2"), "zhōngguó");
+        assert_eq!(tone_marks("ni3 hao3, peng2you5"), "nǐ hǎo, péngyou");
+        assert_eq!(tone_marks("lv4 lu:4 nü3"), "lǜ lǜ nǚ");
+        assert_eq!(tone_marks("Bei3jing1"), "Běijīng");
+        assert_eq!(tone_marks("xie4xie5 shi2 er4 ju1"), "xièxie shí èr jū");
+    }
+
+    #[test]
+    fn leaves_other_tokens_alone() {
+        for text in ["A1", "B2", "mp3", "area51", "go1", "hi5", "covid19", "iphone15", "k8s", "ma", "x86"] {
+            assert_eq!(tone_marks(text), text);
+        }
+    }
+}
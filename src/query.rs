@@ -0,0 +1,458 @@
+// query.rs
+use std::fmt;
+use crate::model::{Card, Deck, Flag};
+use crate::utilities::{current_unix_time, first_line, format_date};
+
+const DAY: u64 = 24 * 60 * 60;
+
+/// A search such as `deck:Korean/grammar tag:verb is:due lapses>3 added:7d "past tense"`.
+/// Every term must match, and a term starting with `-` must not.
+pub struct Query {
+    terms: Vec<(bool, Filter)>, // Whether the term is negated, and what it matches
+}
+
+enum Filter {
+    /// The deck or one of its subdecks
+    Deck(String),
+    Tag(String),
+    Flag(Flag),
+    Is(CardStatus),
+    Compare(Count, Comparison, u64),
+    /// Added within this many seconds
+    Added(u64),
+    /// Text anywhere on the card
+    Text(String),
+}
+
+#[derive(Clone, Copy)]
+enum CardStatus {
+    Due,
+    New,
+    Suspended,
+    Leech,
+    Flagged,
+}
+
+#[derive(Clone, Copy)]
+enum Count {
+    Lapses,
+    Reviews,
+    /// Days between reviews
+    Interval,
+}
+
+#[derive(Clone, Copy)]
+enum Comparison {
+    Less,
+    LessOrEqual,
+    Equal,
+    GreaterOrEqual,
+    Greater,
+}
+
+/// A query that could not be parsed, with the characters of the query it is about.
+#[derive(Debug)]
+pub struct QueryError {
+    pub message: String,
+    pub start: usize,
+    pub end: usize,
+}
+
+// A word of the query with its position in characters, quotes removed
+struct Token {
+    text: String,
+    start: usize,
+    end: usize,
+    // Starts with a quote, so it is searched for as text even if it contains `:`
+    quoted: bool,
+}
+
+impl Query {
+    pub fn parse(query: &str) -> Result<Self, QueryError> {
+        let terms = tokenize(query)?
+            .iter()
+            .map(parse_term)
+            .collect::<Result<_, _>>()?;
+        Ok(Self { terms })
+    }
+
+    /// Narrows the query down to cards with `flag`, or with any flag.
+    pub fn and_flagged(&mut self, flag: Option<Flag>) {
+        self.terms.push((false, match flag {
+            Some(flag) => Filter::Flag(flag),
+            None => Filter::Is(CardStatus::Flagged),
+        }));
+    }
+
+    /// Whether `card`, in the deck named `deck`, matches every term.
+    pub fn matches(&self, card: &Card, deck: &str, current_time: u64) -> bool {
+        self.terms.iter().all(|(negated, filter)| filter.matches(card, deck, current_time) != *negated)
+    }
+}
+
+impl Filter {
+    fn matches(&self, card: &Card, deck: &str, current_time: u64) -> bool {
+        match self {
+            Filter::Deck(name) => {
+                let deck_name = deck.to_lowercase();
+                deck_name == *name || deck_name.starts_with(&format!("{}/", name))
+            }
+            Filter::Tag(tag) => card.tags.iter().any(|card_tag| card_tag.to_lowercase() == *tag),
+            Filter::Is(CardStatus::Due) => card.is_due(current_time),
+            Filter::Is(CardStatus::New) => card.history.as_deref().unwrap_or_default().is_empty(),
+            Filter::Is(CardStatus::Suspended) => card.suspended,
+            Filter::Is(CardStatus::Leech) => card.is_leech(),
+            Filter::Is(CardStatus::Flagged) => card.flag.is_some(),
+            Filter::Flag(flag) => card.flag == Some(*flag),
+            Filter::Compare(count, comparison, value) => {
+                let actual = match count {
+                    Count::Lapses => card.lapses() as u64,
+                    Count::Reviews => card.history.as_deref().unwrap_or_default().len() as u64,
+                    Count::Interval => card.ease_factor.unwrap_or_default() / DAY,
+                };
+                match comparison {
+                    Comparison::Less => actual < *value,
+                    Comparison::LessOrEqual => actual <= *value,
+                    Comparison::Equal => actual == *value,
+                    Comparison::GreaterOrEqual => actual >= *value,
+                    Comparison::Greater => actual > *value,
+                }
+            }
+            Filter::Added(within) => card.added_date()
+                .is_some_and(|added| added.saturating_add(*within) >= current_time),
+            Filter::Text(text) => {
+                let examples = card.examples.iter()
+                    .flatten()
+                    .flat_map(|example| [&example.sentence, &example.translation]);
+                [&card.front, &card.back]
+                    .into_iter()
+                    .chain(card.notes.as_ref())
+                    .chain(card.explanation.as_ref())
+                    .chain(examples)
+                    .any(|field| field.to_lowercase().contains(text))
+            }
+        }
+    }
+}
+
+impl QueryError {
+    fn new(message: impl Into<String>, start: usize, end: usize) -> Self {
+        Self { message: message.into(), start, end }
+    }
+
+    fn at(message: impl Into<String>, token: &Token) -> Self {
+        Self::new(message, token.start, token.end)
+    }
+
+    /// The message followed by the query, with the offending part underlined.
+    pub fn report(&self, query: &str) -> String {
+        let width = |start: usize, end: usize| {
+            let text: String = query.chars().skip(start).take(end - start).collect();
+            ratatui::text::Span::raw(text).width()
+        };
+        format!(
+            "{}\n  {}\n  {}{}",
+            self.message,
+            query,
+            " ".repeat(width(0, self.start)),
+            "^".repeat(width(self.start, self.end).max(1)),
+        )
+    }
+}
+
+impl fmt::Display for QueryError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for QueryError {}
+
+// Splits on whitespace outside of double quotes
+fn tokenize(query: &str) -> Result<Vec<Token>, QueryError> {
+    let mut tokens = Vec::new();
+    let mut current: Option<Token> = None;
+    let mut quote_start = None;
+
+    for (position, c) in query.chars().enumerate() {
+        if c.is_whitespace() && quote_start.is_none() {
+            tokens.extend(current.take());
+            continue;
+        }
+        let token = current.get_or_insert_with(|| Token { text: String::new(), start: position, end: position, quoted: false });
+        token.end = position + 1;
+        if c == '"' {
+            // Only a quote at the start makes the whole token text, so `deck:"My deck"` is still a filter
+            token.quoted |= token.text.is_empty() || token.text == "-";
+            quote_start = match quote_start {
+                Some(_) => None,
+                None => Some(position),
+            };
+        } else {
+            token.text.push(c);
+        }
+    }
+
+    if let Some(start) = quote_start {
+        return Err(QueryError::new("Missing closing quote", start, query.chars().count()));
+    }
+    tokens.extend(current);
+    Ok(tokens)
+}
+
+fn parse_term(token: &Token) -> Result<(bool, Filter), QueryError> {
+    let (negated, text) = match token.text.strip_prefix('-') {
+        Some(rest) if !rest.is_empty() => (true, rest),
+        _ => (false, token.text.as_str()),
+    };
+    // Where `text` starts within the query, for pointing at its parts
+    let offset = token.start + usize::from(negated);
+
+    if token.quoted {
+        return Ok((negated, Filter::Text(text.to_lowercase())));
+    }
+
+    let name_length = text.chars().take_while(char::is_ascii_alphabetic).count();
+    let (name, rest) = text.split_at(name_length);
+    if name.is_empty() {
+        return Ok((negated, Filter::Text(text.to_lowercase())));
+    }
+
+    if let Some(value) = rest.strip_prefix(':') {
+        let value_token = Token {
+            text: value.to_string(),
+            start: offset + name_length + 1,
+            end: token.end,
+            quoted: false,
+        };
+        if value.is_empty() {
+            return Err(QueryError::at(format!("`{}:` needs a value", name), token));
+        }
+        let filter = match name.to_lowercase().as_str() {
+            "deck" => Filter::Deck(value.trim_end_matches('/').to_lowercase()),
+            "tag" => Filter::Tag(value.to_lowercase()),
+            "flag" => Filter::Flag(parse_flag(&value_token)?),
+            "is" => Filter::Is(parse_status(&value_token)?),
+            "added" => Filter::Added(parse_days(&value_token)?),
+            _ => return Err(QueryError::new(
+                format!("Unknown filter `{}:`, expected deck:, tag:, flag:, is: or added:", name),
+                offset,
+                offset + name_length + 1,
+            )),
+        };
+        return Ok((negated, filter));
+    }
+
+    let operator_length = rest.chars().take_while(|c| matches!(c, '<' | '>' | '=')).count();
+    if operator_length == 0 {
+        return Ok((negated, Filter::Text(text.to_lowercase())));
+    }
+    let count = match name.to_lowercase().as_str() {
+        "lapses" => Count::Lapses,
+        "reviews" => Count::Reviews,
+        "interval" => Count::Interval,
+        _ => return Err(QueryError::new(
+            format!("Cannot compare `{}`, expected lapses, reviews or interval", name),
+            offset,
+            offset + name_length,
+        )),
+    };
+    let (operator, value) = rest.split_at(operator_length);
+    let operator_start = offset + name_length;
+    let comparison = match operator {
+        "<" => Comparison::Less,
+        "<=" => Comparison::LessOrEqual,
+        "=" | "==" => Comparison::Equal,
+        ">=" => Comparison::GreaterOrEqual,
+        ">" => Comparison::Greater,
+        _ => return Err(QueryError::new(
+            format!("Unknown comparison `{}`, expected <, <=, =, >= or >", operator),
+            operator_start,
+            operator_start + operator_length,
+        )),
+    };
+    let value_start = operator_start + operator_length;
+    let value = value.parse().map_err(|_| {
+        QueryError::new(format!("Expected a number after `{}{}`", name, operator), value_start, token.end.max(value_start + 1))
+    })?;
+    Ok((negated, Filter::Compare(count, comparison, value)))
+}
+
+fn parse_status(token: &Token) -> Result<CardStatus, QueryError> {
+    match token.text.to_lowercase().as_str() {
+        "due" => Ok(CardStatus::Due),
+        "new" => Ok(CardStatus::New),
+        "suspended" => Ok(CardStatus::Suspended),
+        "leech" => Ok(CardStatus::Leech),
+        "flagged" => Ok(CardStatus::Flagged),
+        _ => Err(QueryError::at(
+            format!("Unknown status `{}`, expected due, new, suspended, leech or flagged", token.text),
+            token,
+        )),
+    }
+}
+
+fn parse_flag(token: &Token) -> Result<Flag, QueryError> {
+    let text = token.text.to_lowercase();
+    Flag::ALL.into_iter()
+        .find(|flag| flag.name() == text)
+        .ok_or_else(|| QueryError::at(
+            format!("Unknown flag `{}`, expected red, orange, green, blue or purple", token.text),
+            token,
+        ))
+}
+
+// `7`, `7d` or `2w`, in seconds
+fn parse_days(token: &Token) -> Result<u64, QueryError> {
+    let text = token.text.to_lowercase();
+    let (number, multiplier) = match text.strip_suffix('w') {
+        Some(weeks) => (weeks, 7 * DAY),
+        None => (text.strip_suffix('d').unwrap_or(&text), DAY),
+    };
+    let number = number.parse::<u64>()
+        .map_err(|_| QueryError::at(format!("Expected a number of days such as 7d or 2w, not `{}`", token.text), token))?;
+    number.checked_mul(multiplier)
+        .ok_or_else(|| QueryError::at(format!("`{}` is too many days", token.text), token))
+}
+
+/// Prints the cards matching `query`, one per line with tabs between the deck,
+/// front, back, due date and flag.
+pub fn list_cards(decks: &[Deck], query: &Query) {
+    let now = current_unix_time();
+    let mut count = 0;
+    for (_, card, deck) in Deck::locate_cards(decks) {
+        if !query.matches(card, &deck.name, now) {
+            continue;
+        }
+        let due = match card.next_review {
+            _ if card.suspended => "suspended".to_string(),
+            Some(next_review) if next_review > now => format_date(next_review),
+            _ => "now".to_string(),
+        };
+        let flag = card.flag.map(Flag::name).unwrap_or_default();
+        println!("{}\t{}\t{}\t{}\t{}", deck.name, first_line(&card.front), first_line(&card.back), due, flag);
+        count += 1;
+    }
+    eprintln!("{} cards", count);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::ReviewHistory;
+
+    const NOW: u64 = 1_800_000_000;
+
+    fn card(front: &str, back: &str) -> Card {
+        let mut card = Card::new(front.to_string(), back.to_string());
+        card.added = Some(NOW - DAY);
+        card
+    }
+
+    fn with_lapses(mut card: Card, lapses: usize) -> Card {
+        card.history = Some((0..lapses)
+            .map(|index| ReviewHistory {
+                date: index as u64,
+                remembered: false,
+                reversed: None,
+                interval: None,
+                partial: None,
+            })
+            .collect());
+        card
+    }
+
+    fn matches(query: &str, card: &Card, deck: &str) -> bool {
+        Query::parse(query).unwrap_or_else(|e| panic!("{}", e.report(query))).matches(card, deck, NOW)
+    }
+
+    fn error(query: &str) -> (usize, usize) {
+        match Query::parse(query) {
+            Ok(_) => panic!("`{}` should not parse", query),
+            Err(e) => (e.start, e.end),
+        }
+    }
+
+    #[test]
+    fn quoted_text_is_one_term() {
+        let homework = card("숙제를 했어요", "I did the past tense homework");
+        assert!(matches("\"past tense\"", &homework, "Korean"));
+        assert!(!matches("\"tense past\"", &homework, "Korean"));
+        // Quoted at the start, a term with a colon is text rather than a filter
+        assert!(matches("\"note:\"", &card("a", "see note: b"), "Korean"));
+        assert!(matches("deck:\"My deck\"", &homework, "My deck/verbs"));
+    }
+
+    #[test]
+    fn negated_terms_must_not_match() {
+        let mut tagged = card("가다", "to go");
+        tagged.tags = vec!["Verb".to_string()];
+        assert!(matches("tag:verb", &tagged, "Korean"));
+        assert!(!matches("-tag:verb", &tagged, "Korean"));
+        assert!(matches("-\"to come\"", &tagged, "Korean"));
+        assert!(!matches("-go", &tagged, "Korean"));
+        // A lone dash is searched for as text
+        assert!(!matches("-", &tagged, "Korean"));
+    }
+
+    #[test]
+    fn comparisons() {
+        let card = with_lapses(card("a", "b"), 3);
+        for (query, expected) in [
+            ("lapses>2", true),
+            ("lapses>3", false),
+            ("lapses>=3", true),
+            ("lapses<3", false),
+            ("lapses<=3", true),
+            ("lapses=3", true),
+            ("lapses==3", true),
+            ("reviews=3", true),
+            ("-lapses=3", false),
+        ] {
+            assert_eq!(matches(query, &card, "Korean"), expected, "{}", query);
+        }
+    }
+
+    #[test]
+    fn deck_includes_subdecks() {
+        let card = card("a", "b");
+        assert!(matches("deck:Korean", &card, "Korean"));
+        assert!(matches("deck:korean", &card, "Korean/grammar"));
+        assert!(matches("deck:Korean/", &card, "Korean/grammar"));
+        assert!(!matches("deck:Korean", &card, "Korean2"));
+        assert!(!matches("deck:Korean/grammar", &card, "Korean"));
+    }
+
+    #[test]
+    fn added_within_days() {
+        let card = card("a", "b");
+        assert!(matches("added:2", &card, "Korean"));
+        assert!(matches("added:1w", &card, "Korean"));
+        assert!(!matches("added:0d", &card, "Korean"));
+    }
+
+    #[test]
+    fn errors_point_at_the_offending_characters() {
+        assert_eq!(error("foo:bar"), (0, 4));
+        assert_eq!(error("-foo:bar"), (1, 5));
+        assert_eq!(error("is:nope"), (3, 7));
+        assert_eq!(error("flag:pink"), (5, 9));
+        assert_eq!(error("tag:"), (0, 4));
+        assert_eq!(error("lapses>>3"), (6, 8));
+        assert_eq!(error("lapses>x"), (7, 8));
+        assert_eq!(error("lapses>"), (7, 8));
+        assert_eq!(error("size>3"), (0, 4));
+        assert_eq!(error("added:soon"), (6, 10));
+        assert_eq!(error("added:99999999999999999w"), (6, 24));
+        assert_eq!(error("a \"open"), (2, 7));
+        // Offsets count characters, not bytes
+        assert_eq!(error("안녕 is:x"), (6, 7));
+    }
+
+    #[test]
+    fn report_underlines_by_display_width() {
+        let query = "안녕 is:x";
+        let e = Query::parse(query).err().expect("should not parse");
+        assert_eq!(e.report(query), format!("{}\n  {}\n  {}^", e.message, query, " ".repeat(8)));
+    }
+}
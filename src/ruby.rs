@@ -0,0 +1,159 @@
+// ruby.rs
+use ratatui::style::{Color, Style};
+use ratatui::text::{Line, Span, Text};
+use serde::Deserialize;
+
+// Pads readings without being dropped or wrapped on like a regular space
+const PADDING: char = '\u{a0}';
+
+/// Where the readings of `{base|reading}` annotations are shown.
+#[derive(Deserialize, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum RubyMode {
+    /// On a line of their own above the text, like furigana
+    #[default]
+    Above,
+    /// In brackets after the text they annotate
+    Beside,
+    Hidden,
+}
+
+impl RubyMode {
+    pub fn next(self) -> Self {
+        match self {
+            RubyMode::Above => RubyMode::Beside,
+            RubyMode::Beside => RubyMode::Hidden,
+            RubyMode::Hidden => RubyMode::Above,
+        }
+    }
+}
+
+enum Segment<'a> {
+    Plain(&'a str),
+    Ruby(&'a str, &'a str),
+}
+
+pub fn has_ruby(text: &str) -> bool {
+    segments(text).iter().any(|segment| matches!(segment, Segment::Ruby(..)))
+}
+
+/// Renders ruby annotations such as `{漢字|かんじ}` in `text`.
+pub fn apply_ruby(text: Text<'static>, mode: RubyMode) -> Text<'static> {
+    let mut lines = Vec::new();
+    for line in text.lines {
+        if !line.spans.iter().any(|span| has_ruby(&span.content)) {
+            lines.push(line);
+            continue;
+        }
+        match mode {
+            RubyMode::Above => {
+                let (readings, base) = readings_above(&line);
+                lines.push(readings);
+                lines.push(base);
+            }
+            RubyMode::Beside | RubyMode::Hidden => lines.push(readings_inline(&line, mode == RubyMode::Beside)),
+        }
+    }
+    Text::from(lines)
+}
+
+// The readings line is padded to the same width as the text, so the two stay
+// lined up when centered. Each annotation takes the width of the wider of its
+// text and reading, with both centered in that space.
+fn readings_above(line: &Line<'static>) -> (Line<'static>, Line<'static>) {
+    let mut base = Vec::new();
+    let mut readings = String::new();
+    let mut base_width = 0;
+    let mut readings_width = 0;
+
+    for span in &line.spans {
+        for segment in segments(&span.content) {
+            match segment {
+                Segment::Plain(text) => {
+                    base.push(Span::styled(text.to_string(), span.style));
+                    base_width += width(text);
+                }
+                Segment::Ruby(text, reading) => {
+                    let cell = width(text).max(width(reading));
+                    base.push(Span::styled(centered(text, cell), span.style));
+                    pad(&mut readings, base_width - readings_width);
+                    readings.push_str(&centered(reading, cell));
+                    base_width += cell;
+                    readings_width = base_width;
+                }
+            }
+        }
+    }
+    pad(&mut readings, base_width - readings_width);
+
+    let mut base = Line::from(base);
+    let mut readings = Line::styled(readings, Style::default().fg(Color::DarkGray));
+    base.alignment = line.alignment;
+    readings.alignment = line.alignment;
+    (readings, base)
+}
+
+fn readings_inline(line: &Line<'static>, show_readings: bool) -> Line<'static> {
+    let mut spans = Vec::new();
+    for span in &line.spans {
+        for segment in segments(&span.content) {
+            match segment {
+                Segment::Plain(text) => spans.push(Span::styled(text.to_string(), span.style)),
+                Segment::Ruby(text, reading) => {
+                    spans.push(Span::styled(text.to_string(), span.style));
+                    if show_readings {
+                        spans.push(Span::styled(format!("({})", reading), Style::default().fg(Color::DarkGray)));
+                    }
+                }
+            }
+        }
+    }
+    let mut result = Line::from(spans);
+    result.alignment = line.alignment;
+    result
+}
+
+fn segments(text: &str) -> Vec<Segment<'_>> {
+    let mut segments = Vec::new();
+    let mut rest = text;
+
+    while let Some(start) = rest.find('{') {
+        let annotation = rest[start + 1..].split_once('}')
+            .and_then(|(inner, _)| inner.split_once('|'))
+            .filter(|(base, reading)| {
+                !base.is_empty() && !reading.is_empty() && !base.contains('{') && !reading.contains('{')
+            });
+        let Some((base, reading)) = annotation else {
+            segments.push(Segment::Plain(&rest[..start + 1]));
+            rest = &rest[start + 1..];
+            continue;
+        };
+        if start > 0 {
+            segments.push(Segment::Plain(&rest[..start]));
+        }
+        segments.push(Segment::Ruby(base, reading));
+        rest = &rest[start + base.len() + reading.len() + 3..];
+    }
+    if !rest.is_empty() {
+        segments.push(Segment::Plain(rest));
+    }
+
+    segments
+}
+
+fn width(text: &str) -> usize {
+    Span::raw(text).width()
+}
+
+fn pad(text: &mut String, width: usize) {
+    text.extend(std::iter::repeat_n(PADDING, width));
+}
+
+fn centered(text: &str, cell: usize) -> String {
+    let space = cell - width(text);
+    let mut result = String::new();
+    pad(&mut result, space / 2);
+    result.push_str(text);
+    pad(&mut result, space - space / 2);
+    result
+}
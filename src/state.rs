@@ -0,0 +1,71 @@
+// state.rs
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use color_eyre::Result;
+use serde::{Deserialize, Serialize};
+use crate::load::get_flashcards_dir;
+use crate::model::{Card, Flag, ReviewHistory};
+
+/// Review state kept outside of the card's own file, for cards whose source
+/// must never be modified (such as cards extracted from notes).
+#[derive(Deserialize, Serialize, Clone, Default)]
+pub struct ReviewState {
+    pub file: String,
+    #[serde(default)]
+    pub history: Option<Vec<ReviewHistory>>,
+    #[serde(default)]
+    pub next_review: Option<u64>,
+    #[serde(default)]
+    pub ease_factor: Option<u64>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub suspended: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub flag: Option<Flag>,
+}
+
+impl ReviewState {
+    pub fn apply_to(&self, card: &mut Card) {
+        card.history = self.history.clone();
+        card.next_review = self.next_review;
+        card.ease_factor = self.ease_factor;
+        card.suspended = self.suspended;
+        card.flag = self.flag;
+    }
+}
+
+/// Directory for kkameokda's own files inside the flashcards directory.
+pub fn data_dir() -> PathBuf {
+    get_flashcards_dir().join(".kkameokda")
+}
+
+fn state_path() -> PathBuf {
+    data_dir().join("state.yaml")
+}
+
+pub fn load_states() -> Result<BTreeMap<String, ReviewState>> {
+    let path = state_path();
+    if !path.exists() {
+        return Ok(BTreeMap::new());
+    }
+    Ok(serde_yaml::from_str(&fs::read_to_string(path)?)?)
+}
+
+pub fn save_states(states: &BTreeMap<String, ReviewState>) -> Result<()> {
+    fs::create_dir_all(data_dir())?;
+    fs::write(state_path(), serde_yaml::to_string(states)?)?;
+    Ok(())
+}
+
+pub fn save_card_state(id: &str, file: &str, card: &Card) -> Result<()> {
+    let mut states = load_states()?;
+    states.insert(id.to_string(), ReviewState {
+        file: file.to_string(),
+        history: card.history.clone(),
+        next_review: card.next_review,
+        ease_factor: card.ease_factor,
+        suspended: card.suspended,
+        flag: card.flag,
+    });
+    save_states(&states)
+}
@@ -0,0 +1,169 @@
+// trash.rs
+use std::fs;
+use std::path::{Path, PathBuf};
+use color_eyre::{eyre::eyre, Result};
+use serde::{Deserialize, Serialize};
+use crate::load::get_flashcards_dir;
+use crate::model::Card;
+use crate::organize::{check_managed, find_notes, rekey_notes, write_card_file};
+use crate::state::data_dir;
+use crate::utilities::{current_unix_time, format_date, relative_path, safe_file_name, unique_card_path};
+
+/// Something moved to the trash. Each entry is a directory in
+/// `.kkameokda/trash` holding `entry.yaml` and the trashed file or deck under
+/// `files/`, at its path within the flashcards directory.
+#[derive(Serialize, Deserialize)]
+pub struct TrashEntry {
+    #[serde(skip)]
+    pub id: String,
+    /// Where it was, relative to the flashcards directory
+    pub path: String,
+    pub deleted: u64,
+}
+
+fn trash_dir() -> PathBuf {
+    data_dir().join("trash")
+}
+
+impl TrashEntry {
+    fn dir(&self) -> PathBuf {
+        trash_dir().join(&self.id)
+    }
+
+    fn files(&self) -> PathBuf {
+        self.dir().join("files").join(&self.path)
+    }
+
+    // Creates the entry's directory and writes down what it holds
+    fn create(path: &str) -> Result<Self> {
+        let deleted = current_unix_time();
+        let mut id = deleted.to_string();
+        let mut suffix = 2;
+        while trash_dir().join(&id).exists() {
+            id = format!("{}-{}", deleted, suffix);
+            suffix += 1;
+        }
+        let entry = Self { id, path: path.to_string(), deleted };
+        fs::create_dir_all(entry.dir())?;
+        fs::write(entry.dir().join("entry.yaml"), serde_yaml::to_string(&entry)?)?;
+        Ok(entry)
+    }
+}
+
+/// Moves a card file or deck directory to the trash.
+pub fn trash_path(path: &Path) -> Result<TrashEntry> {
+    check_managed(path)?;
+    if !path.exists() {
+        return Err(eyre!("{} does not exist", relative_path(path)));
+    }
+    let entry = TrashEntry::create(&relative_path(path))?;
+    let target = entry.files();
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::rename(path, &target)?;
+    Ok(entry)
+}
+
+/// Moves a card to the trash. A card that shares its file with others is
+/// taken out of it and trashed as a file of its own.
+pub fn trash_card(card: &Card) -> Result<TrashEntry> {
+    let Some(path) = &card.file_path else {
+        return Err(eyre!("This card is not stored in a file"));
+    };
+    if card.inline_id.is_some() {
+        return Err(eyre!("Cards inside notes are deleted by editing {}", path.display()));
+    }
+    if card.file_index.is_none() {
+        return trash_path(path);
+    }
+
+    // The entry's directory starts out empty, so the card keeps this name in it
+    let relative = relative_path(path);
+    let deck = relative.rsplit_once('/').map(|(deck, _)| deck).unwrap_or_default();
+    let extension = path.extension().unwrap_or_default().to_string_lossy();
+    let entry = TrashEntry::create(&format!("{}/{}.{}", deck, safe_file_name(&card.front), extension))?;
+    write_card_file(card, path, entry.files().parent().unwrap_or(&entry.dir()))?;
+    card.delete()?;
+    Ok(entry)
+}
+
+/// Everything in the trash, oldest first.
+pub fn list_trash() -> Result<Vec<TrashEntry>> {
+    let mut entries = Vec::new();
+    if !trash_dir().exists() {
+        return Ok(entries);
+    }
+    for dir in fs::read_dir(trash_dir())? {
+        let dir = dir?.path();
+        let entry_path = dir.join("entry.yaml");
+        if !entry_path.exists() {
+            continue;
+        }
+        let mut entry: TrashEntry = serde_yaml::from_str(&fs::read_to_string(&entry_path)?)?;
+        entry.id = dir.file_name().unwrap_or_default().to_string_lossy().to_string();
+        entries.push(entry);
+    }
+    entries.sort_by(|a, b| (a.deleted, &a.id).cmp(&(b.deleted, &b.id)));
+    Ok(entries)
+}
+
+/// Puts a trashed file or deck back where it was. A card file whose name was
+/// taken since is restored under a new name.
+pub fn restore(id: &str) -> Result<PathBuf> {
+    let entry = list_trash()?
+        .into_iter()
+        .find(|entry| entry.id == id)
+        .ok_or_else(|| eyre!("There is nothing in the trash with the ID {}, see kkameokda trash", id))?;
+    let source = entry.files();
+    let original = get_flashcards_dir().join(&entry.path);
+    let target = match original.exists() {
+        false => original.clone(),
+        true if source.is_file() => {
+            let stem = original.file_stem().unwrap_or_default().to_string_lossy();
+            let extension = original.extension().unwrap_or_default().to_string_lossy();
+            unique_card_path(original.parent().unwrap_or(&original), &stem, &extension)
+        }
+        true => return Err(eyre!("{} exists again; move it out of the way first", entry.path)),
+    };
+
+    let notes = find_notes(&source)?;
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::rename(&source, &target)?;
+    // The notes' review state is still kept under their old path
+    if target != original {
+        let notes: Vec<PathBuf> = notes.iter()
+            .map(|note| match note.strip_prefix(&source) {
+                Ok(rest) if !rest.as_os_str().is_empty() => original.join(rest),
+                _ => original.clone(),
+            })
+            .collect();
+        rekey_notes(&original, &target, &notes)?;
+    }
+    fs::remove_dir_all(entry.dir())?;
+    Ok(target)
+}
+
+/// Deletes everything in the trash for good. Returns how many entries there were.
+pub fn empty_trash() -> Result<usize> {
+    let count = list_trash()?.len();
+    if trash_dir().exists() {
+        fs::remove_dir_all(trash_dir())?;
+    }
+    Ok(count)
+}
+
+/// Prints the trash, one entry per line with tabs between the ID, the date it
+/// was deleted and where it was.
+pub fn print_trash() -> Result<()> {
+    let entries = list_trash()?;
+    if entries.is_empty() {
+        eprintln!("The trash is empty");
+    }
+    for entry in entries {
+        println!("{}\t{}\t{}", entry.id, format_date(entry.deleted), entry.path);
+    }
+    Ok(())
+}
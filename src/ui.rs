@@ -5,14 +5,24 @@ use ratatui::{
     Frame,
 };
 use ratatui::prelude::*;
-use crate::model::{Card};
+use crate::config::Config;
+use crate::answer::AnswerCheck;
+use crate::hangul::{contains_hangul, romanize};
+use crate::highlight::has_code_block;
+use crate::images::CardImage;
+use crate::input::TextInput;
+use crate::markdown::markdown_text;
+use crate::pinyin::tone_marks;
+use crate::ruby::{apply_ruby, has_ruby, RubyMode};
+use crate::model::{Card, Flag};
 use ratatui::widgets::{
     block::{Position, Title},
-    Block,
+    Block, Scrollbar, ScrollbarOrientation, ScrollbarState,
 };
 use ratatui::style::{Color, Style};
+use ratatui_image::{Resize, StatefulImage};
 
-fn popup_area(area: Rect, percent_x: u16, percent_y: u16) -> Rect {
+pub fn popup_area(area: Rect, percent_x: u16, percent_y: u16) -> Rect {
     let vertical = Layout::vertical([Constraint::Percentage(percent_y)]).flex(Flex::Center);
     let horizontal = Layout::horizontal([Constraint::Percentage(percent_x)]).flex(Flex::Center);
     let [area] = vertical.areas(area);
@@ -20,22 +30,39 @@ fn popup_area(area: Rect, percent_x: u16, percent_y: u16) -> Rect {
     area
 }
 
-pub fn draw_frame(f: &mut Frame, remaining: usize, remembered: u32, forgotten: u32, current_deck: Option<&str>, show_shortcuts: bool) {
+pub fn flag_color(flag: Flag) -> Color {
+    match flag {
+        Flag::Red => Color::Red,
+        Flag::Orange => Color::Indexed(208),
+        Flag::Green => Color::Green,
+        Flag::Blue => Color::LightBlue,
+        Flag::Purple => Color::Magenta,
+    }
+}
+
+/// What the frame's title shows about the current card.
+pub struct CardTitle<'a> {
+    pub deck: &'a str,
+    /// The card's flag with its label
+    pub flag: Option<(Flag, &'a str)>,
+}
+
+pub fn draw_frame(f: &mut Frame, remaining: usize, remembered: u32, forgotten: u32, current_card: Option<CardTitle>, show_shortcuts: bool, message: Option<&str>) {
+    let mut title = vec![
+        Span::raw(" 까먹다 "),
+        Span::raw("│ "),
+        Span::styled(
+            format!(" {} ", current_card.as_ref().map_or("No Deck", |card| card.deck)),
+            Style::default().fg(Color::Yellow)
+        ),
+        Span::raw(" "),
+    ];
+    if let Some((flag, label)) = current_card.and_then(|card| card.flag) {
+        title.push(Span::styled(format!("⚑ {} ", label), Style::default().fg(flag_color(flag))));
+    }
     let mut main_block = Block::default()
         .borders(Borders::ALL)
-        .title(
-            Title::from(
-                Line::from(vec![
-                    Span::raw(" 까먹다 "),
-                    Span::raw("│ "),
-                    Span::styled(
-                        format!(" {} ", current_deck.unwrap_or("No Deck")),
-                        Style::default().fg(Color::Yellow)
-                    ),
-                    Span::raw(" "),                
-                ])
-            )
-        );
+        .title(Title::from(Line::from(title)));
 
     // Simplified shortcuts string with just the ? key
     let shortcuts = Line::from(vec![
@@ -72,11 +99,19 @@ pub fn draw_frame(f: &mut Frame, remaining: usize, remembered: u32, forgotten: u
         .position(Position::Bottom)
     );
     
+    if let Some(message) = message {
+        main_block = main_block.title(
+            Title::from(Span::styled(format!(" {} ", message), Style::default().fg(Color::Red)))
+                .alignment(Alignment::Center)
+                .position(Position::Bottom)
+        );
+    }
+
     f.render_widget(main_block, f.area());
 
     // Render shortcuts popup if enabled
     if show_shortcuts {
-        let area = popup_area(f.area(), 60, 40);
+        let area = popup_area(f.area(), 60, 70);
         f.render_widget(Clear, area);
         
     let shortcuts_text = vec![
@@ -103,7 +138,55 @@ pub fn draw_frame(f: &mut Frame, remaining: usize, remembered: u32, forgotten: u
         ]),
         Line::from(vec![
             Span::styled("Ctrl+e", Style::default().fg(Color::Yellow)),
-            Span::raw(": Open current flashcard"),
+            Span::raw(": Edit current flashcard in $EDITOR"),
+        ]),
+        Line::from(vec![
+            Span::styled("a", Style::default().fg(Color::Yellow)),
+            Span::raw(": Add a new card"),
+        ]),
+        Line::from(vec![
+            Span::styled("e", Style::default().fg(Color::Yellow)),
+            Span::raw(": Edit card fields"),
+        ]),
+        Line::from(vec![
+            Span::styled("n", Style::default().fg(Color::Yellow)),
+            Span::raw(": Add to the card's notes"),
+        ]),
+        Line::from(vec![
+            Span::styled("1-5/0", Style::default().fg(Color::Yellow)),
+            Span::raw(": Flag red/orange/green/blue/purple, or clear"),
+        ]),
+        Line::from(vec![
+            Span::styled("b", Style::default().fg(Color::Yellow)),
+            Span::raw(": Browse all cards"),
+        ]),
+        Line::from(vec![
+            Span::styled("r", Style::default().fg(Color::Yellow)),
+            Span::raw(": Toggle romanization"),
+        ]),
+        Line::from(vec![
+            Span::styled("y", Style::default().fg(Color::Yellow)),
+            Span::raw(": Readings above/beside/hidden"),
+        ]),
+        Line::from(vec![
+            Span::styled("p", Style::default().fg(Color::Yellow)),
+            Span::raw(": Play audio"),
+        ]),
+        Line::from(vec![
+            Span::styled("P", Style::default().fg(Color::Yellow)),
+            Span::raw(": Speak example sentence"),
+        ]),
+        Line::from(vec![
+            Span::styled("←/→", Style::default().fg(Color::Yellow)),
+            Span::raw(": Scroll code sideways"),
+        ]),
+        Line::from(vec![
+            Span::styled("Tab", Style::default().fg(Color::Yellow)),
+            Span::raw(": Select panel to scroll"),
+        ]),
+        Line::from(vec![
+            Span::styled("↑/↓/PgUp/PgDn", Style::default().fg(Color::Yellow)),
+            Span::raw(": Scroll panel (or use the mouse wheel)"),
         ]),
     ];
             
@@ -117,118 +200,144 @@ pub fn draw_frame(f: &mut Frame, remaining: usize, remembered: u32, forgotten: u
     }
 }
 
-pub fn draw_hint(f: &mut Frame, card: &Card, reversed: bool) {
-    let area = f.area();
-    let inner_area = Block::default()
-        .borders(Borders::ALL)
-        .inner(area);
+/// The parts of a card that can be scrolled independently.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Panel {
+    Front,
+    Back,
+    Explanation,
+    Examples,
+    Notes,
+}
 
-    let inner_layout = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints(vec![
-            Constraint::Percentage(30),
-            Constraint::Max(1),      
-            Constraint::Min(0),      
-        ])
-        .split(inner_area);
-
-    if reversed {
-        f.render_widget(
-            Paragraph::new(card.back.as_str())
-                .alignment(Alignment::Center),
-            inner_layout[1]
-        );
-    } else {
-        f.render_widget(
-            Paragraph::new(card.front.as_str())
-                .alignment(Alignment::Center),
-            inner_layout[1]
-        );
+/// Scroll positions for the card being shown, and how its text is displayed.
+#[derive(Default)]
+pub struct CardView {
+    scroll: [u16; 5],
+    pub focus: Option<Panel>,
+    pub code_scroll: u16,
+    // Display toggles, kept when moving on to the next card
+    pub romanize: bool,
+    pub ruby: RubyMode,
+}
+
+impl CardView {
+    pub fn scroll(&self, panel: Panel) -> u16 {
+        self.scroll[panel as usize]
+    }
+
+    pub fn set_scroll(&mut self, panel: Panel, scroll: u16) {
+        self.scroll[panel as usize] = scroll;
+    }
+
+    /// A fresh view for the next card, with the same display toggles.
+    pub fn for_next_card(&self) -> Self {
+        Self { romanize: self.romanize, ruby: self.ruby, ..Self::default() }
     }
 }
 
-pub fn draw_full(f: &mut Frame, card: &Card, reversed: bool) {
-    let area = f.area();
+/// A typed answer: still being written, or checked against the card.
+pub enum Answer<'a> {
+    Typing(&'a TextInput),
+    Checked(&'a str, &'a AnswerCheck),
+}
 
-    // Get inner area accounting for the main frame's borders
-    let inner_area = Block::default()
-        .borders(Borders::ALL)
-        .inner(area);
+struct CardAreas {
+    image: Rect,
+    front: Rect,
+    back: Rect,
+    // The line below the back, where a typed answer's result is shown
+    answer: Rect,
+    info: Rect,
+}
 
-    // Get layout for front
-    let inner_layout = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints(vec![
-            Constraint::Percentage(30),
-            Constraint::Max(1),      // Front text
-            Constraint::Min(0),      
-        ])
-        .split(inner_area);
-
-    // Draw front
-    if reversed {
-        f.render_widget(
-            Paragraph::new(card.back.as_str())
-                .alignment(Alignment::Center)
-                .wrap(Wrap { trim: true }),
-            inner_layout[1]
-        );
-    } else {
-        f.render_widget(
-            Paragraph::new(card.front.as_str())
-                .alignment(Alignment::Center)
-                .wrap(Wrap { trim: true }),
-            inner_layout[1]
-        );
+/// Where a panel was drawn, and how far it can scroll.
+pub struct PanelArea {
+    pub panel: Panel,
+    pub area: Rect,
+    pub height: u16,
+    pub max_scroll: u16,
+}
+
+pub fn draw_hint(f: &mut Frame, card: &Card, reversed: bool, config: &Config, view: &CardView, image: Option<&mut CardImage>, answer: Option<Answer>) -> Vec<PanelArea> {
+    let (front, back) = if reversed { (&card.back, &card.front) } else { (&card.front, &card.back) };
+    let layout = card_layout(f.area(), front, back, config, view, image.is_some());
+
+    if let Some(image) = image {
+        render_image(f, image, layout.image);
+    }
+    let mut areas = Vec::new();
+    render_panel(f, front, layout.front, Panel::Front, config, view, &mut areas);
+    if let Some(answer) = answer {
+        render_answer(f, answer, layout.back);
     }
+    areas
+}
 
-    // Get layout for back
-    let back_layout = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints(vec![
-            Constraint::Percentage(10),
-            Constraint::Percentage(10),
-            Constraint::Percentage(40),
-            Constraint::Percentage(40),
-        ])
-        .split(inner_layout[2]);
-
-    // Draw back
-    if reversed {
-        f.render_widget(
-            Paragraph::new(card.front.as_str())
-                .alignment(Alignment::Center)
-                .wrap(Wrap { trim: true }),
-            back_layout[1]
-        );
-    } else {
-        f.render_widget(
-            Paragraph::new(card.back.as_str())
-                .alignment(Alignment::Center)
-                .wrap(Wrap { trim: true }),
-            back_layout[1]
-        );
+/// The hint screen of listening practice: only the typed answer, if any.
+pub fn draw_listening(f: &mut Frame, answer: Option<Answer>) {
+    if let Some(answer) = answer {
+        let [_, area, _] = Layout::vertical([Constraint::Fill(1), Constraint::Length(1), Constraint::Fill(1)]).areas(f.area());
+        render_answer(f, answer, area.inner(Margin { vertical: 0, horizontal: 1 }));
     }
+}
+
+/// Line breaks are shown as this while a field is edited on a single line.
+pub const LINE_BREAK: char = '⏎';
+
+/// Draws a one-line input over the bottom of the screen, such as the one for
+/// editing a field of the card.
+pub fn draw_line_input(f: &mut Frame, title: &str, help: &str, input: &TextInput) {
+    let screen = f.area();
+    let area = Rect {
+        x: screen.x + 2,
+        y: screen.bottom().saturating_sub(4),
+        width: screen.width.saturating_sub(4),
+        height: 3.min(screen.height),
+    };
+    f.render_widget(Clear, area);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Double)
+        .title(Span::styled(format!(" {} ", title), Style::default().fg(Color::Yellow)))
+        .title(Title::from(format!(" {} ", help)).position(Position::Bottom));
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    let scroll = input.scroll(inner.width);
+    f.render_widget(Paragraph::new(input.value.as_str()).scroll((0, scroll)), inner);
+    f.set_cursor_position((inner.x + input.cursor_offset() - scroll, inner.y));
+}
+
+pub fn draw_full(f: &mut Frame, card: &Card, reversed: bool, config: &Config, view: &CardView, image: Option<&mut CardImage>, answer: Option<Answer>) -> Vec<PanelArea> {
+    let (front, back) = if reversed { (&card.back, &card.front) } else { (&card.front, &card.back) };
+    let layout = card_layout(f.area(), front, back, config, view, image.is_some());
+    let info_area = layout.info;
+
+    if let Some(image) = image {
+        render_image(f, image, layout.image);
+    }
+    let mut areas = Vec::new();
+    render_panel(f, front, layout.front, Panel::Front, config, view, &mut areas);
+    render_panel(f, back, layout.back, Panel::Back, config, view, &mut areas);
+    if let Some(answer) = answer {
+        render_answer(f, answer, layout.answer);
+    }
+
     // Get layout for info
     let info_layout = Layout::default()
         .direction(Direction::Horizontal)
         .constraints(create_dynamic_constraints(
             card.explanation.is_some(),
-            !card.examples.as_ref().map_or(true, |e| e.is_empty()),
+            card.examples.as_ref().is_some_and(|e| !e.is_empty()),
             card.notes.is_some()
         ))
-        .split(back_layout[3]);
+        .split(info_area);
 
     let mut rendered_sections = 0;
 
     if let Some(explanation) = &card.explanation {
-        f.render_widget(
-            Paragraph::new(explanation.as_str())
-                .wrap(Wrap { trim: true })
-                .block(Block::new().title("Explanation").borders(Borders::ALL))
-                .alignment(Alignment::Center),
-            info_layout[rendered_sections]
-        );
+        render_panel(f, explanation, info_layout[rendered_sections], Panel::Explanation, config, view, &mut areas);
         rendered_sections += 1;
     }
 
@@ -238,26 +347,194 @@ pub fn draw_full(f: &mut Frame, card: &Card, reversed: bool) {
                 .map(|ex| format!("{} - {}\n", ex.sentence, ex.translation))
                 .collect::<String>();
             
-            f.render_widget(
-                Paragraph::new(examples_text.as_str())
-                    .wrap(Wrap { trim: true })
-                    .block(Block::new().title("Examples").borders(Borders::ALL))
-                    .alignment(Alignment::Center),
-                info_layout[rendered_sections]
-            );
+            render_panel(f, &examples_text, info_layout[rendered_sections], Panel::Examples, config, view, &mut areas);
             rendered_sections += 1;
         }
     }
 
     if let Some(notes) = &card.notes {
-        f.render_widget(
-            Paragraph::new(notes.as_str())
-                .wrap(Wrap { trim: true })
-                .block(Block::new().title("Notes").borders(Borders::ALL))
-                .alignment(Alignment::Center),
-            info_layout[rendered_sections]
+        render_panel(f, notes, info_layout[rendered_sections], Panel::Notes, config, view, &mut areas);
+    }
+
+    areas
+}
+
+// Front and back get as many rows as their text needs, up to a quarter of the
+// screen each, and the info panels get the rest. The front stays where it is
+// when the card is turned over. A card image takes a third of the screen above
+// the front, in place of the padding.
+fn card_layout(area: Rect, front: &str, back: &str, config: &Config, view: &CardView, has_image: bool) -> CardAreas {
+    // Get inner area accounting for the main frame's borders
+    let inner_area = Block::default()
+        .borders(Borders::ALL)
+        .inner(area);
+
+    let max_height = (inner_area.height / 4).max(1);
+    let text_height = |text: &str, panel: Panel| {
+        (field_paragraph(text, config, view, panel).line_count(inner_area.width) as u16).clamp(1, max_height)
+    };
+    let front_height = text_height(front, Panel::Front);
+    let back_height = text_height(back, Panel::Back);
+
+    let (top_padding, image_height) = if has_image {
+        (1, inner_area.height / 3)
+    } else {
+        ((inner_area.height * 3 / 10).saturating_sub(front_height - 1), 0)
+    };
+
+    let [_, image, _, front, _, back, answer, info] = Layout::vertical([
+        Constraint::Length(top_padding),
+        Constraint::Length(image_height),
+        Constraint::Length(u16::from(has_image)),
+        Constraint::Length(front_height),
+        Constraint::Length(1),
+        Constraint::Length(back_height),
+        Constraint::Length(1),
+        Constraint::Min(0),
+    ]).areas(inner_area);
+
+    CardAreas { image, front, back, answer, info }
+}
+
+fn render_answer(f: &mut Frame, answer: Answer, area: Rect) {
+    let line = match answer {
+        Answer::Typing(input) => {
+            let width = (input.cursor_offset() + 2).max(area.width / 3).min(area.width);
+            let x = area.x + (area.width - width) / 2;
+            f.set_cursor_position((x + 1 + input.cursor_offset().min(width.saturating_sub(2)), area.y));
+            f.render_widget(
+                Paragraph::new(Line::from(vec![Span::raw(" "), Span::raw(input.value.as_str())]))
+                    .style(Style::default().bg(Color::DarkGray)),
+                Rect { x, width, height: 1, ..area },
+            );
+            return;
+        }
+        Answer::Checked(typed, AnswerCheck::Correct) => Line::from(vec![
+            Span::styled("✓ ", Style::default().fg(Color::Green)),
+            Span::raw(typed.to_string()),
+        ]),
+        Answer::Checked(typed, AnswerCheck::Close(similarity)) => Line::from(vec![
+            Span::styled(format!("≈ {:.0}% ", similarity * 100.0), Style::default().fg(Color::Yellow)),
+            Span::raw(typed.to_string()),
+            Span::styled("  (Enter: partial credit)", Style::default().fg(Color::DarkGray)),
+        ]),
+        Answer::Checked(typed, AnswerCheck::Wrong) => Line::from(vec![
+            Span::styled("✗ ", Style::default().fg(Color::Red)),
+            Span::styled(typed.to_string(), Style::default().add_modifier(Modifier::CROSSED_OUT)),
+        ]),
+    };
+    f.render_widget(Paragraph::new(line).alignment(Alignment::Center), area);
+}
+
+// Scales the image down to fit, keeping its proportions, and centers it
+fn render_image(f: &mut Frame, image: &mut CardImage, area: Rect) {
+    match &mut image.image {
+        Ok(encoded) => {
+            let scale = (area.width as f64 / encoded.width.max(1) as f64)
+                .min(area.height as f64 / encoded.height.max(1) as f64)
+                .min(1.0);
+            let width = ((encoded.width as f64 * scale) as u16).max(1);
+            let height = ((encoded.height as f64 * scale) as u16).max(1);
+            let [image_area] = Layout::horizontal([Constraint::Length(width)]).flex(Flex::Center).areas(area);
+            let [image_area] = Layout::vertical([Constraint::Length(height)]).flex(Flex::Center).areas(image_area);
+            f.render_stateful_widget(StatefulImage::new(None).resize(Resize::Fit(None)), image_area, &mut encoded.protocol);
+        }
+        Err(error) => {
+            let message = Paragraph::new(error.as_str())
+                .style(Style::default().fg(Color::Red))
+                .alignment(Alignment::Center)
+                .wrap(Wrap { trim: true });
+            f.render_widget(message, area);
+        }
+    }
+}
+
+// Draws one part of the card, scrolled to its position in `view`, with a
+// scrollbar when the text does not fit
+fn render_panel(f: &mut Frame, text: &str, area: Rect, panel: Panel, config: &Config, view: &CardView, areas: &mut Vec<PanelArea>) {
+    let title = match panel {
+        Panel::Front | Panel::Back => None,
+        Panel::Explanation => Some("Explanation"),
+        Panel::Examples => Some("Examples"),
+        Panel::Notes => Some("Notes"),
+    };
+    let block = title.map(|title| {
+        let border_color = if view.focus == Some(panel) { Color::Yellow } else { Color::Reset };
+        Block::new()
+            .title(title)
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(border_color))
+    });
+    let inner_area = block.as_ref().map_or(area, |block| block.inner(area));
+
+    let paragraph = field_paragraph(text, config, view, panel);
+    let content_height = paragraph.line_count(inner_area.width) as u16;
+    let max_scroll = content_height.saturating_sub(inner_area.height);
+    let scroll = view.scroll(panel).min(max_scroll);
+    let code_scroll = if has_code_block(text) { view.code_scroll } else { 0 };
+
+    let mut paragraph = paragraph.scroll((scroll, code_scroll));
+    if let Some(block) = block {
+        paragraph = paragraph.block(block);
+    }
+    f.render_widget(paragraph, area);
+
+    if max_scroll > 0 {
+        let mut state = ScrollbarState::new(max_scroll as usize).position(scroll as usize);
+        f.render_stateful_widget(
+            Scrollbar::new(ScrollbarOrientation::VerticalRight).begin_symbol(None).end_symbol(None),
+            area.inner(Margin { vertical: u16::from(title.is_some()), horizontal: 0 }),
+            &mut state,
         );
     }
+
+    areas.push(PanelArea { panel, area, height: inner_area.height, max_scroll });
+}
+
+fn card_text(text: &str, config: &Config) -> Text<'static> {
+    if config.markdown {
+        markdown_text(text, &config.code_theme)
+    } else {
+        Text::raw(text.to_string())
+    }
+}
+
+// Text with fenced code is left-aligned and not wrapped so the code keeps its
+// layout, and scrolls sideways instead
+fn field_paragraph(text: &str, config: &Config, view: &CardView, panel: Panel) -> Paragraph<'static> {
+    let code = has_code_block(text);
+    let mut card_text = if config.pinyin_tone_marks && !code {
+        card_text(&tone_marks(text), config)
+    } else {
+        card_text(text, config)
+    };
+    if has_ruby(text) && !code {
+        card_text = apply_ruby(card_text, view.ruby);
+    }
+    // Explanations and notes are mostly in the language being learned from
+    let romanize = view.romanize && matches!(panel, Panel::Front | Panel::Back | Panel::Examples);
+    if romanize && contains_hangul(text) && !code {
+        card_text = with_romanization(card_text);
+    }
+    let paragraph = Paragraph::new(card_text);
+    if has_code_block(text) {
+        paragraph.alignment(Alignment::Left)
+    } else {
+        paragraph.alignment(Alignment::Center).wrap(Wrap { trim: true })
+    }
+}
+
+// Adds the romanization of each line with Hangul below it
+fn with_romanization(text: Text<'static>) -> Text<'static> {
+    let mut lines = Vec::new();
+    for line in text.lines {
+        let plain: String = line.spans.iter().map(|span| span.content.as_ref()).collect();
+        lines.push(line);
+        if contains_hangul(&plain) {
+            lines.push(Line::styled(romanize(&plain), Style::default().fg(Color::DarkGray).add_modifier(Modifier::ITALIC)));
+        }
+    }
+    Text::from(lines)
 }
 
 fn create_dynamic_constraints(has_explanation: bool, has_examples: bool, has_notes: bool) -> Vec<Constraint> {
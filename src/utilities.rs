@@ -1,5 +1,7 @@
 // utilities.rs
+use std::path::{Path, PathBuf};
 use std::time::{SystemTime, UNIX_EPOCH};
+use crate::load::get_flashcards_dir;
 use crate::model::Deck;
 
 pub fn current_unix_time() -> u64 {
@@ -14,7 +16,7 @@ pub fn print_deck_structure(deck: &Deck, indent: usize) {
     println!("{}└─ {} ({} cards)", indent_str, deck.name, deck.cards.len());
     
     for subdeck in &deck.subdecks {
-        let subdeck_name = subdeck.name.split('/').last().unwrap_or(&subdeck.name);
+        let subdeck_name = subdeck.name.split('/').next_back().unwrap_or(&subdeck.name);
         let indent_str = " ".repeat(indent + 2);
         println!("{}└─ {} ({} cards)", indent_str, subdeck_name, subdeck.cards.len());
     }
@@ -30,4 +32,91 @@ pub fn print_session_summary(remembered: u32, forgotten: u32) {
             (remembered as f64 / total as f64 * 100.0)
         );
     }
-}
\ No newline at end of file
+}
+
+/// FNV-1a hash, used where an ID must stay the same across runs and builds.
+pub fn stable_hash(text: &str) -> u64 {
+    text.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// Turns card or deck text into something safe to use as a file or directory name.
+pub fn safe_file_name(text: &str) -> String {
+    let mut name = String::new();
+    for c in text.trim().chars() {
+        if c.is_alphanumeric() {
+            name.extend(c.to_lowercase());
+        } else if (c.is_whitespace() || c == '-' || c == '_') && !name.ends_with('_') {
+            name.push('_');
+        }
+        if name.chars().count() >= 60 {
+            break;
+        }
+    }
+    let name = name.trim_matches('_');
+    if name.is_empty() {
+        "card".to_string()
+    } else {
+        name.to_string()
+    }
+}
+
+/// Picks a path for a new card file in `dir`, adding a numeric suffix when
+/// a card with the same name already exists.
+pub fn unique_card_path(dir: &Path, front: &str, extension: &str) -> PathBuf {
+    let base = safe_file_name(front);
+    let mut path = dir.join(format!("{}.{}", base, extension));
+    let mut suffix = 2;
+    while path.exists() {
+        path = dir.join(format!("{}_{}.{}", base, suffix, extension));
+        suffix += 1;
+    }
+    path
+}
+
+pub fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Formats a UNIX timestamp as a `YYYY-MM-DD` date (UTC).
+pub fn format_date(timestamp: u64) -> String {
+    // Civil-from-days conversion, see http://howardhinnant.github.io/date_algorithms.html
+    let days = (timestamp / 86400) as i64 + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days.rem_euclid(146097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 { month_index + 3 } else { month_index - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+/// Path relative to the flashcards directory, with `/` separators.
+pub fn relative_path(path: &Path) -> String {
+    let flashcards_dir = get_flashcards_dir();
+    path.strip_prefix(&flashcards_dir)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .replace('\\', "/")
+}
+
+/// Formats a number of seconds as a short duration such as `45m`, `6h` or `12d`.
+pub fn format_interval(seconds: u64) -> String {
+    match seconds {
+        0..3600 => format!("{}m", seconds / 60),
+        3600..86400 => format!("{}h", seconds / 3600),
+        86400..2592000 => format!("{}d", seconds / 86400),
+        _ => format!("{}mo", seconds / 2592000),
+    }
+}
+
+/// The first line of a card field, for showing it in a single row.
+pub fn first_line(text: &str) -> String {
+    text.lines().next().unwrap_or_default().to_string()
+}